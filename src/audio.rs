@@ -0,0 +1,770 @@
+use crate::config::AudioEntry;
+use rodio::cpal::traits::HostTrait;
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, DeviceTrait, OutputStream, OutputStreamHandle, Sink, Source};
+use std::{collections::HashMap, error::Error, fmt, fs, io::BufReader, path::Path, time::Duration};
+
+/// Sound file extensions the enabled rodio decoders handle, matched
+/// case-insensitively.
+const SUPPORTED_FORMATS: [&str; 4] = ["wav", "flac", "ogg", "mp3"];
+
+/// Most sounds waiting in the serialized queue; later ones are dropped so a
+/// burst of triggers can't keep talking long after the moment has passed.
+const MAX_QUEUED: usize = 3;
+
+/// Peak level, as a fraction of full scale, that `normalize` brings every
+/// cached sound to. Just under full scale leaves a little headroom.
+const NORMALIZED_PEAK: f32 = 0.9;
+
+/// Range a per-entry `gain_db` is clamped to.
+const MAX_GAIN_DB: f32 = 24.0;
+
+/// Range a per-entry `speed` is clamped to; past it sounds turn to noise or a drone.
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+/// Memory the decoded sound cache may use; sounds past it are decoded from
+/// disk each time they play instead.
+const MAX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Owns the audio output for the lifetime of the app, so triggers don't
+/// open a new device (and spawn a new thread) for every sound.
+pub struct AudioPlayer {
+    // Dropping the stream ends all playback, so it has to outlive every sink.
+    _stream: Option<OutputStream>,
+    handle: Option<OutputStreamHandle>,
+    // Shared sink that plays sounds back-to-back; `None` mixes them instead
+    queue: Option<Sink>,
+    // Whether `queue` is wanted, kept while there's no output to create it on
+    serialized: bool,
+    // The looping alert and its callout's name, until dismissed
+    alert: Option<(String, Sink)>,
+    // Background track and its path, looping until stopped
+    ambience: Option<(String, Sink)>,
+    // Output device picked by name; `None` uses the system default
+    device: Option<String>,
+    // Set when `device` changed, so the stream is reopened before the next sound
+    reopen: bool,
+    // Never opens an output, so nothing is heard
+    offline: bool,
+    // Applied to every sound unless its entry sets its own
+    fade: Fade,
+    cache: SoundCache,
+    // Played in place of a sound that fails to play
+    fallback: Option<String>,
+    // Scale cached sounds to the same peak level
+    normalize: bool,
+    #[cfg(feature = "tts")]
+    tts: Option<tts::Tts>,
+    // Every sound file asked to play, in order, so tests can tell without a device
+    #[cfg(test)]
+    pub played: Vec<String>,
+}
+
+impl AudioPlayer {
+    /// Opens the output device named `device`, or the default one.
+    pub fn new(device: Option<String>) -> Self {
+        let (stream, handle) = open_output(device.as_deref()).unzip();
+
+        Self {
+            _stream: stream,
+            handle,
+            device,
+            offline: false,
+            #[cfg(feature = "tts")]
+            tts: tts::Tts::default()
+                .map_err(|err| eprintln!("Text-to-speech unavailable: {err}"))
+                .ok(),
+            ..Self::offline()
+        }
+    }
+
+    /// A player that never opens an output device or speech, for a timer
+    /// nobody should hear.
+    pub fn offline() -> Self {
+        Self {
+            _stream: None,
+            handle: None,
+            queue: None,
+            serialized: false,
+            alert: None,
+            ambience: None,
+            device: None,
+            reopen: false,
+            offline: true,
+            fade: Fade::default(),
+            cache: SoundCache::default(),
+            fallback: None,
+            normalize: false,
+            #[cfg(test)]
+            played: Vec::new(),
+            #[cfg(feature = "tts")]
+            tts: None,
+        }
+    }
+
+    /// Routes playback to the output device named `device`, or the default
+    /// one. The stream is reopened lazily on the next sound.
+    pub fn set_device(&mut self, device: Option<String>) {
+        if self.device != device {
+            self.device = device;
+            self.reopen = true;
+        }
+    }
+
+    pub fn device(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+
+    /// Whether there is an output device to play on. Without one the clock
+    /// still runs, and each sound retries opening the device first.
+    pub fn has_output(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Reopens the stream after a device change, or when there was no output
+    /// yet in case a device has been connected since.
+    fn reopen_if_needed(&mut self) {
+        if self.offline || (!std::mem::take(&mut self.reopen) && self.handle.is_some()) {
+            return;
+        }
+        // The queue, alert and ambience belong to the old stream, so they have to follow it
+        self.queue = None;
+        self.alert = None;
+        self.ambience = None;
+        self.handle = None;
+        self._stream = None;
+        let (stream, handle) = open_output(self.device.as_deref()).unzip();
+        self._stream = stream;
+        self.handle = handle;
+        self.set_serialized(self.serialized);
+    }
+
+    /// Decodes `paths` into memory ahead of time so they play without delay,
+    /// replacing whatever was cached for the previous config.
+    pub fn preload<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        self.cache = SoundCache::default();
+        for path in paths {
+            if let Err(err) = self.cache.insert(path) {
+                eprintln!("Not caching {path}, it will be read when played: {err}");
+            }
+        }
+    }
+
+    /// Sets the sound played when another one fails. One that can't be
+    /// played itself is rejected, so failures don't cascade.
+    pub fn set_fallback(&mut self, fallback: Option<String>) {
+        self.fallback = fallback.filter(|path| match check_playable(path) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Ignoring fallback sound {path}: {err}");
+                false
+            }
+        });
+    }
+
+    pub fn has_fallback(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    pub fn set_normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    pub fn fade(&self) -> Fade {
+        self.fade
+    }
+
+    pub fn set_fade(&mut self, fade: Fade) {
+        self.fade = fade;
+    }
+
+    /// Switches between mixing overlapping sounds (the default) and queueing
+    /// them to play one after another.
+    pub fn set_serialized(&mut self, serialized: bool) {
+        self.serialized = serialized;
+        if !serialized {
+            // Let whatever is queued finish instead of cutting it off
+            if let Some(queue) = self.queue.take() {
+                queue.detach();
+            }
+            return;
+        }
+        if self.queue.is_none() {
+            if let Some(handle) = &self.handle {
+                self.queue = Sink::try_new(handle)
+                    .map_err(|err| eprintln!("Failed to create audio queue: {err}"))
+                    .ok();
+            }
+        }
+    }
+
+    /// Plays an entry's sound files in order, or speaks its `say` text when it
+    /// has none. `volume` is the master volume, scaled by the entry's own when
+    /// it has one.
+    pub fn play_entry(&mut self, entry: &AudioEntry, volume: f32) {
+        let volume = entry_volume(entry, volume);
+        let fade = Fade {
+            fade_in: entry.fade_in_ms.map_or(self.fade.fade_in, millis),
+            fade_out: entry.fade_out_ms.map_or(self.fade.fade_out, millis),
+        };
+        let finite = |value: &f32| value.is_finite();
+        let effects = Effects {
+            gain: entry.gain_db.map_or(1.0, db_to_gain),
+            speed: entry
+                .speed
+                .filter(finite)
+                .map_or(1.0, |speed| speed.clamp(MIN_SPEED, MAX_SPEED)),
+            pan: entry.pan.filter(finite).map_or(0.0, |pan| pan.clamp(-1.0, 1.0)),
+        };
+        let paths: Vec<&str> = entry.sounds().collect();
+        match (paths.is_empty(), &entry.say) {
+            (false, _) if entry.looping => {
+                self.play_alert(&entry.name(), &paths, volume, fade, effects)
+            }
+            (false, _) => self.play_faded(&paths, volume, fade, effects),
+            (true, Some(text)) => self.say(text, volume),
+            (true, None) => {}
+        }
+    }
+
+    #[cfg(feature = "tts")]
+    pub fn say(&mut self, text: &str, volume: f32) {
+        let Some(tts) = &mut self.tts else {
+            eprintln!("Text-to-speech unavailable, skipping {text:?}");
+            return;
+        };
+
+        if tts.supported_features().volume {
+            let (min, max) = (tts.min_volume(), tts.max_volume());
+            let _ = tts.set_volume(min + (max - min) * volume.clamp(0.0, 1.0));
+        }
+        if let Err(err) = tts.speak(text, false) {
+            eprintln!("Failed to say {text:?}: {err}");
+        }
+    }
+
+    #[cfg(not(feature = "tts"))]
+    pub fn say(&mut self, text: &str, _volume: f32) {
+        eprintln!("Built without the `tts` feature, skipping {text:?}");
+    }
+
+    /// Starts playing `path` at `volume` (0.0–1.0) without blocking. Unless
+    /// serialized, each sound gets its own sink, so triggers landing on the
+    /// same second are mixed rather than dropped.
+    pub fn play(&mut self, path: &str, volume: f32) {
+        self.play_faded(&[path], volume, self.fade, Effects::NONE);
+    }
+
+    /// Plays `paths` back-to-back on one sink, so a sequence keeps its order
+    /// and isn't cut short by the next trigger.
+    fn play_faded(&mut self, paths: &[&str], volume: f32, fade: Fade, effects: Effects) {
+        #[cfg(test)]
+        self.played.extend(paths.iter().map(|path| path.to_string()));
+        self.reopen_if_needed();
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        let sources = self.sources(paths, fade, effects);
+        if sources.is_empty() {
+            return;
+        }
+
+        let result = match &self.queue {
+            Some(queue) => Self::try_enqueue(queue, sources, volume),
+            None => Self::try_play(handle, sources, volume),
+        };
+        if let Err(err) = result {
+            eprintln!("Failed to play {}: {err}", paths.join(", "));
+        }
+    }
+
+    /// Plays `paths` over and over until dismissed, replacing the previous
+    /// alert. It gets a sink of its own even when serialized, so it never
+    /// holds up the queue.
+    fn play_alert(
+        &mut self,
+        name: &str,
+        paths: &[&str],
+        volume: f32,
+        fade: Fade,
+        effects: Effects,
+    ) {
+        #[cfg(test)]
+        self.played.extend(paths.iter().map(|path| path.to_string()));
+        self.dismiss_alert();
+        self.reopen_if_needed();
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        let sources = self.sources(paths, fade, effects);
+        if sources.is_empty() {
+            return;
+        }
+
+        match Sink::try_new(handle) {
+            Ok(sink) => {
+                sink.set_volume(volume.clamp(0.0, 1.0));
+                sink.append(rodio::source::from_iter(sources).repeat_infinite());
+                self.alert = Some((name.to_string(), sink));
+            }
+            Err(err) => eprintln!("Failed to play {}: {err}", paths.join(", ")),
+        }
+    }
+
+    /// The callout whose alert is looping, if any.
+    pub fn alert(&self) -> Option<&str> {
+        self.alert.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Stops the looping alert.
+    pub fn dismiss_alert(&mut self) {
+        if let Some((_, sink)) = self.alert.take() {
+            sink.stop();
+        }
+    }
+
+    /// Loops `path` in the background at `volume` until stopped, replacing
+    /// any other ambience. It is read from disk as it plays, since it is
+    /// likely too long to keep in memory.
+    pub fn play_ambience(&mut self, path: &str, volume: f32) {
+        self.stop_ambience();
+        // Only a device change reopens here, as this is retried on every update
+        if self.reopen {
+            self.reopen_if_needed();
+        }
+        let Some(handle) = &self.handle else {
+            return;
+        };
+        let owned = path.to_string();
+        // Decoded afresh for every pass
+        let track = rodio::source::from_factory(move || {
+            let file = fs::File::open(&owned).ok()?;
+            Decoder::new(BufReader::new(file)).ok()
+        });
+        match Sink::try_new(handle) {
+            Ok(sink) => {
+                sink.set_volume(volume.clamp(0.0, 1.0));
+                sink.append(track);
+                self.ambience = Some((path.to_string(), sink));
+            }
+            Err(err) => eprintln!("Failed to play ambience {path}: {err}"),
+        }
+    }
+
+    /// The path of the ambience playing (or held), if any.
+    pub fn ambience(&self) -> Option<&str> {
+        self.ambience.as_ref().map(|(path, _)| path.as_str())
+    }
+
+    /// Changes the ambience's volume, and holds or continues it.
+    pub fn update_ambience(&self, volume: f32, paused: bool) {
+        if let Some((_, sink)) = &self.ambience {
+            sink.set_volume(volume.clamp(0.0, 1.0));
+            if paused {
+                sink.pause();
+            } else {
+                sink.play();
+            }
+        }
+    }
+
+    pub fn stop_ambience(&mut self) {
+        if let Some((_, sink)) = self.ambience.take() {
+            sink.stop();
+        }
+    }
+
+    /// Decodes `paths` with their effects. A broken part is swapped for the
+    /// fallback, the rest still play.
+    fn sources(&self, paths: &[&str], fade: Fade, effects: Effects) -> Vec<Sound> {
+        let prepare = |path: &str, effects: Effects| {
+            let gain = self.cache.safe_gain(path, effects.gain, self.normalize);
+            decode(path, fade, effects.speed, &self.cache).map(|mut source| -> Sound {
+                if gain != 1.0 {
+                    source = Box::new(source.amplify(gain));
+                }
+                if effects.pan != 0.0 && source.channels() <= 2 {
+                    source = Box::new(Pan::new(source, effects.pan));
+                }
+                source
+            })
+        };
+
+        let mut sources = Vec::with_capacity(paths.len());
+        for path in paths {
+            match prepare(path, effects) {
+                Ok(source) => sources.push(source),
+                Err(err) => {
+                    eprintln!("Failed to play {path}: {err}");
+                    if let Some(fallback) = self.fallback.as_deref() {
+                        match prepare(fallback, Effects::NONE) {
+                            Ok(source) => sources.push(source),
+                            Err(err) => eprintln!("Failed to play fallback {fallback}: {err}"),
+                        }
+                    }
+                }
+            }
+        }
+        sources
+    }
+
+    /// A sequence is appended as one chained source, so it counts as one
+    /// sound against `MAX_QUEUED` and is never queued in part.
+    fn try_enqueue(queue: &Sink, sources: Vec<Sound>, volume: f32) -> Result<(), Box<dyn Error>> {
+        if queue.len() >= MAX_QUEUED {
+            return Err("audio queue is full".into());
+        }
+        // The sink's volume is shared, so scale each sound on its own
+        queue.append(rodio::source::from_iter(sources).amplify(volume.clamp(0.0, 1.0)));
+        Ok(())
+    }
+
+    fn try_play(
+        handle: &OutputStreamHandle,
+        sources: Vec<Sound>,
+        volume: f32,
+    ) -> Result<(), Box<dyn Error>> {
+        let sink = Sink::try_new(handle)?;
+        sink.set_volume(volume.clamp(0.0, 1.0));
+        for source in sources {
+            sink.append(source);
+        }
+        sink.detach();
+        Ok(())
+    }
+}
+
+/// How long a sound takes to fade in from silence and back out at its end,
+/// to soften abrupt clips. Zero means no fade.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Fade {
+    pub fade_in: Duration,
+    pub fade_out: Duration,
+}
+
+/// Per-entry changes to how a sound plays.
+#[derive(Debug, Clone, Copy)]
+struct Effects {
+    /// Linear boost or cut on top of the volume, adjusted for normalization
+    /// and limited so the sound doesn't clip.
+    gain: f32,
+    /// Changes pitch and length together.
+    speed: f32,
+    /// -1.0 is fully left, 1.0 fully right.
+    pan: f32,
+}
+
+impl Effects {
+    const NONE: Effects = Effects { gain: 1.0, speed: 1.0, pan: 0.0 };
+}
+
+pub fn millis(ms: u32) -> Duration {
+    Duration::from_millis(ms.into())
+}
+
+/// Converts decibels to a linear gain, clamped to ±`MAX_GAIN_DB`.
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB) / 20.0)
+}
+
+type Sound = Box<dyn Source<Item = i16> + Send>;
+
+/// Decodes `path`, from `cache` when it's there, at `speed` and with `fade`
+/// applied. Fades are timed after the speed change, so they keep their
+/// length. A clip shorter than its fades gets at most half its length for
+/// each, so it still plays in full.
+fn decode(path: &str, fade: Fade, speed: f32, cache: &SoundCache) -> Result<Sound, Box<dyn Error>> {
+    let mut source: Sound = match cache.source(path) {
+        Some(source) => Box::new(source),
+        None => Box::new(Decoder::new(BufReader::new(fs::File::open(path)?))?),
+    };
+    if speed != 1.0 {
+        source = Box::new(source.speed(speed));
+    }
+    // Fading out needs to know where the end is, which not every format reports
+    let length = source.total_duration();
+    let half = length.map_or(Duration::MAX, |length| length / 2);
+    let fade_in = fade.fade_in.min(half);
+    let fade_out = length.map_or(Duration::ZERO, |_| fade.fade_out.min(half));
+
+    let source = FadeOutTail::new(source, length.unwrap_or_default(), fade_out);
+    Ok(if fade_in.is_zero() {
+        Box::new(source)
+    } else {
+        Box::new(source.fade_in(fade_in))
+    })
+}
+
+/// Sounds decoded ahead of time, so the first play doesn't wait on the disk.
+#[derive(Default)]
+struct SoundCache {
+    sounds: HashMap<String, Decoded>,
+    bytes: usize,
+}
+
+struct Decoded {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<i16>,
+    // Loudest sample, as a fraction of full scale
+    peak: f32,
+}
+
+impl SoundCache {
+    /// Decodes `path` into the cache, unless that would go over `MAX_CACHE_BYTES`.
+    fn insert(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        if self.sounds.contains_key(path) {
+            return Ok(());
+        }
+        let mut decoder = Decoder::new(BufReader::new(fs::File::open(path)?))?;
+        let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+
+        // Stop decoding as soon as it's clear the sound won't fit
+        let budget = (MAX_CACHE_BYTES - self.bytes) / size_of::<i16>();
+        let samples: Vec<i16> = decoder.by_ref().take(budget + 1).collect();
+        if samples.len() > budget {
+            return Err("cache is full".into());
+        }
+
+        self.bytes += samples.len() * size_of::<i16>();
+        let peak = samples.iter().map(|sample| sample.unsigned_abs()).max().unwrap_or(0);
+        let decoded = Decoded {
+            channels,
+            sample_rate,
+            samples,
+            peak: f32::from(peak) / f32::from(i16::MAX),
+        };
+        self.sounds.insert(path.to_string(), decoded);
+        Ok(())
+    }
+
+    /// `gain` for `path`, times the normalizing gain when `normalize` is on,
+    /// capped where the loudest sample would clip. Only cached sounds have a
+    /// known peak, so other sounds are never boosted.
+    fn safe_gain(&self, path: &str, gain: f32, normalize: bool) -> f32 {
+        let peak = self.sounds.get(path).map(|sound| sound.peak);
+        let Some(peak) = peak.filter(|peak| *peak > 0.0) else {
+            return gain.min(1.0);
+        };
+        let normalized = if normalize { NORMALIZED_PEAK / peak } else { 1.0 };
+        (gain * normalized).min(1.0 / peak)
+    }
+
+    fn source(&self, path: &str) -> Option<SamplesBuffer<i16>> {
+        let sound = self.sounds.get(path)?;
+        Some(SamplesBuffer::new(sound.channels, sound.sample_rate, sound.samples.clone()))
+    }
+}
+
+/// Scales the last `fade` of a source down to silence. rodio's own
+/// `fade_out` starts at the beginning of the source instead.
+struct FadeOutTail<S> {
+    input: S,
+    // Samples (across all channels) left in the source, and in the fade
+    remaining: u64,
+    fade: u64,
+}
+
+impl<S: Source<Item = i16>> FadeOutTail<S> {
+    fn new(input: S, length: Duration, fade: Duration) -> Self {
+        let rate = f64::from(input.sample_rate()) * f64::from(input.channels());
+        Self {
+            remaining: (length.as_secs_f64() * rate) as u64,
+            fade: (fade.as_secs_f64() * rate) as u64,
+            input,
+        }
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for FadeOutTail<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.input.next()?;
+        self.remaining = self.remaining.saturating_sub(1);
+        if self.remaining >= self.fade {
+            return Some(sample);
+        }
+        Some((f32::from(sample) * self.remaining as f32 / self.fade as f32) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for FadeOutTail<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Places a mono or stereo source between the speakers, as stereo. Panning
+/// only turns the far side down, so a sound panned fully to one side plays
+/// there at the same level as it would centered.
+struct Pan<S> {
+    input: S,
+    mono: bool,
+    left: f32,
+    right: f32,
+    // A mono sample's copy for the right channel, still to be returned
+    pending: Option<i16>,
+    // Whether the next stereo input sample is a left one
+    on_left: bool,
+}
+
+impl<S: Source<Item = i16>> Pan<S> {
+    fn new(input: S, pan: f32) -> Self {
+        Self {
+            mono: input.channels() == 1,
+            left: (1.0 - pan).min(1.0),
+            right: (1.0 + pan).min(1.0),
+            input,
+            pending: None,
+            on_left: true,
+        }
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Pan<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if let Some(right) = self.pending.take() {
+            return Some(right);
+        }
+        let sample = f32::from(self.input.next()?);
+        if self.mono {
+            self.pending = Some((sample * self.right) as i16);
+            return Some((sample * self.left) as i16);
+        }
+        let gain = if self.on_left { self.left } else { self.right };
+        self.on_left = !self.on_left;
+        Some((sample * gain) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Pan<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        let len = self.input.current_frame_len()?;
+        Some(if self.mono { len * 2 + usize::from(self.pending.is_some()) } else { len })
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Opens `device` by name, falling back to the default output when it is
+/// unset or no longer connected.
+fn open_output(device: Option<&str>) -> Option<(OutputStream, OutputStreamHandle)> {
+    let named = device.and_then(|name| {
+        let found = rodio::cpal::default_host()
+            .output_devices()
+            .ok()?
+            .find(|candidate| candidate.name().is_ok_and(|candidate| candidate == name));
+        if found.is_none() {
+            eprintln!("Audio device {name:?} not found, using the default output");
+        }
+        found
+    });
+
+    let result = match &named {
+        Some(device) => OutputStream::try_from_device(device),
+        None => OutputStream::try_default(),
+    };
+    result
+        .map_err(|err| eprintln!("Failed to open audio output: {err}"))
+        .ok()
+}
+
+/// Names of the connected output devices, for the device picker.
+pub fn output_devices() -> Vec<String> {
+    let devices = match rodio::cpal::default_host().output_devices() {
+        Ok(devices) => devices,
+        Err(err) => {
+            eprintln!("Failed to list audio devices: {err}");
+            return Vec::new();
+        }
+    };
+    devices.filter_map(|device| device.name().ok()).collect()
+}
+
+/// Whether `path` has the extension of a format the decoders handle.
+pub fn is_supported(path: &Path) -> bool {
+    crate::config::extension(path).is_some_and(|ext| SUPPORTED_FORMATS.contains(&ext.as_str()))
+}
+
+/// What `entry` plays at with the master volume at `master`: scaled by the
+/// entry's own volume, which the config's `default_volume` fills in.
+pub fn entry_volume(entry: &AudioEntry, master: f32) -> f32 {
+    master * entry.volume.map_or(1.0, |own| own.clamp(0.0, 1.0))
+}
+
+/// Checks that `path` exists and can be decoded, without playing it.
+pub fn check_playable(path: &str) -> Result<(), Box<dyn Error>> {
+    if !is_supported(Path::new(path)) {
+        let extension = crate::config::extension(Path::new(path));
+        let found = extension.map_or("no extension".to_string(), |ext| format!(".{ext}"));
+        return Err(format!("unsupported format ({found}); use WAV, FLAC, OGG or MP3").into());
+    }
+    let file = fs::File::open(path)?;
+    Decoder::new(BufReader::new(file))?;
+    Ok(())
+}
+
+impl Default for AudioPlayer {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl fmt::Debug for AudioPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AudioPlayer")
+            .field("available", &self.handle.is_some())
+            .field("serialized", &self.serialized)
+            .field("alert", &self.alert())
+            .field("ambience", &self.ambience())
+            .field("device", &self.device)
+            .field("cached", &self.cache.sounds.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn silence() -> Sound {
+        Box::new(SamplesBuffer::new(1, 44_100, vec![0i16; 441]))
+    }
+
+    #[test]
+    fn sequence_takes_one_place_in_the_queue() {
+        let (queue, _output) = Sink::new_idle();
+        let sequence = vec![silence(), silence(), silence()];
+        AudioPlayer::try_enqueue(&queue, sequence, 1.0).unwrap();
+        assert_eq!(queue.len(), 1);
+        AudioPlayer::try_enqueue(&queue, vec![silence()], 1.0).unwrap();
+        AudioPlayer::try_enqueue(&queue, vec![silence()], 1.0).unwrap();
+        assert!(AudioPlayer::try_enqueue(&queue, vec![silence()], 1.0).is_err());
+    }
+}
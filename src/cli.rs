@@ -0,0 +1,70 @@
+use crate::config::Config;
+
+pub const USAGE: &str = "\
+Usage: dota-timer [--config <file>] [--autostart] [--overlay-port <port>] [--speed <factor>]
+                  [--title <text>]
+
+Options:
+  -c, --config <file>  Load this strategy config on launch
+  -a, --autostart      Start the countdown right away
+      --overlay-port <port>
+                       Serve the clock for stream overlays on this local port
+      --speed <factor> Run the clock this many times faster, to check a config
+  -t, --title <text>   Window title, to tell several timers apart
+  -h, --help           Show this help";
+
+/// Command-line options for launching alongside the game from a script.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub config: Option<String>,
+    pub autostart: bool,
+    pub overlay_port: Option<u16>,
+    pub speed: Option<f32>,
+    pub title: Option<String>,
+}
+
+impl Args {
+    /// Parses the arguments after the program name. `Ok(None)` means help
+    /// was asked for.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-c" | "--config" => {
+                    let file = args.next().ok_or("--config needs a file path")?;
+                    parsed.config = Some(file);
+                }
+                "-a" | "--autostart" => parsed.autostart = true,
+                "--overlay-port" => {
+                    let port = args.next().ok_or("--overlay-port needs a port number")?;
+                    let port = port.parse().map_err(|_| format!("Invalid port {port:?}"))?;
+                    parsed.overlay_port = Some(port);
+                }
+                "--speed" => {
+                    let speed = args.next().ok_or("--speed needs a factor")?;
+                    let speed = speed
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|speed| *speed > 0.0)
+                        .ok_or_else(|| format!("Invalid speed {speed:?}"))?;
+                    parsed.speed = Some(speed);
+                }
+                "-t" | "--title" => {
+                    let title = args.next().ok_or("--title needs some text")?;
+                    parsed.title = Some(title);
+                }
+                "-h" | "--help" => return Ok(None),
+                other => return Err(format!("Unknown argument {other:?}")),
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+/// Reads and parses `file` up front, so a bad path fails the launch with a
+/// message instead of opening a timer with nothing loaded.
+pub fn check_config(file: &str) -> Result<(), String> {
+    Config::load(file).map(drop).map_err(|err| err.to_string())
+}
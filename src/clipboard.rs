@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// The system clipboard, opened on the first copy and then kept open: on
+/// Linux the copied text is only on offer while it stays open.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Puts `text` on the clipboard, replacing what was there.
+    pub fn write(&mut self, text: &str) -> Result<(), arboard::Error> {
+        let mut clipboard = match self.inner.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new()?,
+        };
+        let result = clipboard.set_text(text);
+        self.inner = Some(clipboard);
+        result
+    }
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clipboard").field("open", &self.inner.is_some()).finish()
+    }
+}
@@ -0,0 +1,1017 @@
+use crate::hotkeys::HotkeyConfig;
+use crate::theme::AppTheme;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::Path,
+    time::Duration,
+};
+
+/// Default length of the pre-game countdown before the horn.
+pub const PREGAME_COUNTDOWN: Duration = Duration::from_secs(90);
+
+/// Second past each minute when jungle camps should be pulled to stack.
+const STACK_SECOND: u16 = 53;
+
+/// Last game second the rune presets are scheduled up to.
+const PRESET_HORIZON: u16 = 90 * 60;
+
+/// How long each day and each night lasts on the game clock.
+pub const DAY_NIGHT_SECS: u16 = 5 * 60;
+
+/// The game's recurring events, which `events` entries, the rune presets and
+/// the built-in warnings are all scheduled from: (name, label, first second,
+/// interval, last second). One-off events end where they start.
+const EVENTS: [(&str, &str, u16, u16, u16); 7] = [
+    ("bounty_rune", "Bounty runes", 0, 3 * 60, PRESET_HORIZON),
+    ("power_rune", "Power rune", 6 * 60, 2 * 60, PRESET_HORIZON),
+    ("wisdom_rune", "Wisdom runes", 7 * 60, 7 * 60, PRESET_HORIZON),
+    ("water_rune", "Water runes", 2 * 60, 2 * 60, 4 * 60),
+    ("lotus", "Healing lotus", 3 * 60, 3 * 60, PRESET_HORIZON),
+    ("day_night", "Day/night", DAY_NIGHT_SECS, DAY_NIGHT_SECS, PRESET_HORIZON),
+    ("tormentor", "Tormentor", 15 * 60, 1, 15 * 60),
+];
+
+/// The `EVENTS` entry named `name`.
+fn known_event(name: &str) -> Option<(&'static str, &'static str, u16, u16, u16)> {
+    EVENTS.iter().find(|(known, ..)| *known == name).copied()
+}
+
+/// Every second `event` happens at, from its first up to its last.
+fn event_seconds((.., first, interval, last): (&str, &str, u16, u16, u16)) -> Vec<i32> {
+    (i32::from(first)..=i32::from(last)).step_by(usize::from(interval)).collect()
+}
+
+/// The built-in warnings that can be ticked in the window whatever config is
+/// loaded: (name in `EVENTS`, label).
+pub const WARNINGS: [(&str, &str); 4] = [
+    ("bounty_rune", "Bounty"),
+    ("power_rune", "Power"),
+    ("wisdom_rune", "Wisdom"),
+    ("day_night", "Day/night"),
+];
+
+/// How far ahead of its event a warning plays.
+pub const WARNING_LEAD_SECS: u16 = 10;
+
+/// The countdown beep, and the warnings' sound, when the config names none.
+pub const DEFAULT_BEEP_SOUND: &str = "./beep.wav";
+
+/// Longest countdown a quick timer may run.
+const MAX_QUICK_TIMER: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// File extensions picked up as strategy configs, matched case-insensitively.
+const CONFIG_EXTENSIONS: [&str; 4] = ["yaml", "yml", "json", "toml"];
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub countdown_secs: Option<u16>,
+    /// Start straight at the horn, for custom games or syncing mid-match.
+    #[serde(default)]
+    pub skip_countdown: bool,
+    /// Callouts keyed by game-clock second, measured from the horn: `0` plays
+    /// at the horn and negative seconds play during the pre-game countdown.
+    #[serde(default)]
+    pub audio: HashMap<TriggerTime, AudioSpec>,
+    #[serde(default)]
+    pub repeat: Vec<RepeatRule>,
+    /// Callouts scheduled by game event name instead of by second, e.g.
+    /// `tormentor`, expanded into every time the event happens.
+    #[serde(default)]
+    pub events: Vec<EventEntry>,
+    /// Seconds early every callout plays, to leave time to react (0 by default).
+    #[serde(default)]
+    pub lead_time_secs: u16,
+    /// Keep callouts timed at or after the horn out of the countdown, even
+    /// when a lead or heads-up would move them into it (on when absent).
+    /// Negative keys always play during the countdown.
+    #[serde(default)]
+    pub running_only: Option<bool>,
+    /// Seconds before every callout to give a heads-up as well (0, the
+    /// default, gives none).
+    #[serde(default)]
+    pub pre_announce_secs: u16,
+    /// Played for each heads-up; without it the heads-up is spoken, e.g.
+    /// "Power rune in 10".
+    #[serde(default)]
+    pub pre_announce_sound: Option<String>,
+    /// Named alternative strategies; the chosen one's callouts are added to
+    /// the shared `audio` and `repeat` above.
+    #[serde(default)]
+    pub profiles: HashMap<String, AudioSet>,
+    /// Played when the minimum Roshan respawn window opens.
+    #[serde(default)]
+    pub rosh_sound: Option<String>,
+    /// Played when a glyph, scan, buyback or smoke cooldown runs out.
+    #[serde(default)]
+    pub cooldown_sound: Option<String>,
+    /// Buttons for auxiliary countdowns, replacing the built-in glyph, scan,
+    /// buyback and smoke ones when given. Also accepted as `marks`, for
+    /// marking unscheduled events such as an enemy smoke.
+    #[serde(default, alias = "marks")]
+    pub quick_timers: Vec<QuickTimerSpec>,
+    /// Tint the time display briefly when a callout plays (on by default).
+    #[serde(default)]
+    pub flash: Option<bool>,
+    /// Flash color as `#rrggbb`, amber by default.
+    #[serde(default)]
+    pub flash_color: Option<String>,
+    /// Played instead of a callout whose own file is missing or can't be decoded.
+    #[serde(default)]
+    pub fallback_sound: Option<String>,
+    /// File that every fired callout is appended to, with game and wall-clock time.
+    #[serde(default)]
+    pub log_file: Option<String>,
+    /// Played once when the countdown ends and the game clock starts.
+    #[serde(default)]
+    pub horn_sound: Option<String>,
+    /// Beep at 3, 2 and 1 seconds before the horn.
+    #[serde(default)]
+    pub countdown_beeps: bool,
+    #[serde(default)]
+    pub beep_sound: Option<String>,
+    /// Played at :53 of every minute as a jungle stacking reminder.
+    #[serde(default)]
+    pub stack_sound: Option<String>,
+    /// First minute that gets a stacking reminder (0 by default).
+    #[serde(default)]
+    pub stack_from_minute: Option<u16>,
+    /// Chime played at every whole minute of the game clock, or every
+    /// `chime_interval_secs` when set.
+    #[serde(default)]
+    pub minute_chime: Option<String>,
+    #[serde(default)]
+    pub chime_interval_secs: Option<u16>,
+    /// Background track looped while the game clock runs.
+    #[serde(default)]
+    pub ambience: Option<String>,
+    /// Loudness of the ambience (0.0–1.0) under the master volume, 0.3 by default.
+    #[serde(default)]
+    pub ambience_volume: Option<f32>,
+    /// Start over by itself once the game clock reaches `end_secs`, for
+    /// practicing the same stretch of a game again and again.
+    #[serde(default)]
+    pub auto_loop: bool,
+    #[serde(default)]
+    pub end_secs: Option<u32>,
+    /// Show whether it is day or night in the game, and how long until it turns.
+    #[serde(default)]
+    pub day_night: bool,
+    /// Played at every turn from day to night and back, with `day_night` on.
+    #[serde(default)]
+    pub day_night_sound: Option<String>,
+    /// Played for the built-in warnings ticked in the window; the
+    /// `beep_sound`, or `./beep.wav`, when absent.
+    #[serde(default)]
+    pub warning_sound: Option<String>,
+    /// Update the clock every 10ms, for callouts at fractional seconds.
+    #[serde(default)]
+    pub precise_timing: bool,
+    /// Fade every sound in and out over this many milliseconds (0 by default).
+    #[serde(default)]
+    pub fade_in_ms: u32,
+    #[serde(default)]
+    pub fade_out_ms: u32,
+    /// Volume (0.0–1.0) for callouts that don't set their own.
+    #[serde(default)]
+    pub default_volume: Option<f32>,
+    /// Fade in and out, in milliseconds, for callouts that don't set their
+    /// own; unlike `fade_in_ms` it leaves the horn, beeps and timers alone.
+    #[serde(default)]
+    pub default_fade: Option<u32>,
+    /// Bring every sound to the same peak level as it is loaded.
+    #[serde(default)]
+    pub normalize: bool,
+    /// Play overlapping callouts one after another instead of mixing them.
+    #[serde(default)]
+    pub serialize_audio: bool,
+    /// Pause while the timer's window is in the background, for tabbing out
+    /// to other programs (off by default, since the game itself takes focus).
+    #[serde(default)]
+    pub pause_on_unfocus: bool,
+    /// Folder of sounds the editor lists for picking, relative to the config
+    /// file (`sounds` by default).
+    #[serde(default)]
+    pub sound_library: Option<String>,
+    /// Adds the standard bounty, power and wisdom rune callouts.
+    #[serde(default)]
+    pub rune_presets: bool,
+    #[serde(default)]
+    pub bounty_rune_sound: Option<String>,
+    #[serde(default)]
+    pub power_rune_sound: Option<String>,
+    #[serde(default)]
+    pub wisdom_rune_sound: Option<String>,
+    #[serde(default)]
+    pub hotkeys: HotkeyConfig,
+    /// Window background opacity (0.1–1.0); left unchanged when absent.
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    /// Color theme (`default`, `light`, `dark` or `dota`); left unchanged when absent.
+    #[serde(default)]
+    pub theme: Option<AppTheme>,
+    /// Window title while this config is loaded, e.g. the strategy's name,
+    /// to tell several timers apart.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Time display font size (12–96); left unchanged when absent.
+    #[serde(default)]
+    pub font_size: Option<f32>,
+    /// Scales the whole window (0.5–3.0), on top of the system's display
+    /// scaling; left unchanged when absent.
+    #[serde(default)]
+    pub ui_scale: Option<f32>,
+    /// Show the signed in-game clock ("-0:45", "12:34") instead of "00:45";
+    /// left unchanged when absent.
+    #[serde(default)]
+    pub game_clock: Option<bool>,
+    /// Show the time of day while idle or paused; left unchanged when absent.
+    #[serde(default)]
+    pub wall_clock: Option<bool>,
+    /// Advanced: how often the clock updates while running, in milliseconds.
+    #[serde(default)]
+    pub tick_ms: Option<u64>,
+    /// Advanced: how many seconds late a trigger may still play after the
+    /// timer stalled, e.g. through sleep (10 by default).
+    #[serde(default)]
+    pub late_grace_secs: Option<u16>,
+}
+
+/// A `quick_timers` item, kept even when malformed so one bad entry is
+/// skipped with a warning instead of failing the whole config.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum QuickTimerSpec {
+    Timer(QuickTimer),
+    Malformed(de::IgnoredAny),
+}
+
+/// A config-defined button that starts an auxiliary countdown.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuickTimer {
+    pub label: String,
+    /// Seconds, or `m:ss`.
+    pub duration: TimerLength,
+    /// Played when it runs out, instead of `cooldown_sound`.
+    #[serde(default)]
+    pub sound: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TimerLength {
+    Secs(f64),
+    Clock(String),
+}
+
+impl QuickTimer {
+    /// The countdown's length, if it's positive and at most `MAX_QUICK_TIMER`.
+    pub fn length(&self) -> Option<Duration> {
+        let length = match &self.duration {
+            TimerLength::Secs(secs) => Duration::try_from_secs_f64(*secs).ok()?,
+            TimerLength::Clock(clock) => crate::parse_clock(clock)?,
+        };
+        (!length.is_zero() && length <= MAX_QUICK_TIMER).then_some(length)
+    }
+}
+
+/// A callout that recurs every `interval` seconds from `start`, up to and
+/// including `end` when given.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RepeatRule {
+    pub start: u16,
+    pub interval: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<u16>,
+    pub sound: String,
+}
+
+impl RepeatRule {
+    /// One rule per entry of `WARNINGS`, in the same order, playing `sound`.
+    pub fn warnings(sound: &str) -> Vec<RepeatRule> {
+        WARNINGS
+            .iter()
+            .filter_map(|(name, _)| known_event(name))
+            .map(|(.., first, interval, _)| {
+                // One for an event at the horn would be due before it
+                let first = if first < WARNING_LEAD_SECS { first + interval } else { first };
+                RepeatRule {
+                    start: first - WARNING_LEAD_SECS,
+                    interval,
+                    end: None,
+                    sound: sound.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.interval > 0 && self.end.map_or(true, |end| end >= self.start)
+    }
+
+    /// Whether one of this rule's occurrences falls on `second`.
+    pub fn fires_at(&self, second: u64) -> bool {
+        let start = u64::from(self.start);
+        second >= start
+            && self.end.map_or(true, |end| second <= u64::from(end))
+            && (second - start) % u64::from(self.interval) == 0
+    }
+
+    /// The sound's file name, for display and logging.
+    pub fn name(&self) -> String {
+        Path::new(&self.sound).file_stem().map_or_else(
+            || self.sound.clone(),
+            |stem| stem.to_string_lossy().into_owned(),
+        )
+    }
+
+    /// The first occurrence at or after `second`, unless the rule has ended.
+    pub fn next_from(&self, second: u64) -> Option<u64> {
+        let (start, interval) = (u64::from(self.start), u64::from(self.interval));
+        let next = start + second.saturating_sub(start).div_ceil(interval) * interval;
+        self.end.map_or(true, |end| next <= u64::from(end)).then_some(next)
+    }
+
+    /// Every occurrence of this rule strictly before `second`.
+    pub fn occurrences_before(&self, second: u64) -> impl Iterator<Item = u64> {
+        let last = self.end.map_or(second, |end| second.min(u64::from(end) + 1));
+        (u64::from(self.start)..last).step_by(self.interval.into())
+    }
+}
+
+impl Config {
+    /// Reads and parses the config at `file`, with its sound paths resolved
+    /// against the file's folder.
+    pub fn load(file: &str) -> Result<Config, LoadError> {
+        let contents = fs::read_to_string(file).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => LoadError::NotFound(file.to_string()),
+            _ => LoadError::Read(file.to_string(), err),
+        })?;
+        let mut config =
+            Config::parse(file, &contents).map_err(|err| LoadError::Parse(file.to_string(), err))?;
+        if let Some(dir) = Path::new(file).parent() {
+            config.resolve_paths(dir);
+        }
+        Ok(config)
+    }
+
+    /// Parses `contents` with the format matching the extension of `path`.
+    pub fn parse(path: &str, contents: &str) -> Result<Config, ParseError> {
+        match extension(Path::new(path)).as_deref() {
+            Some("json") => serde_json::from_str(contents).map_err(|err| {
+                let location = (err.line() > 0).then(|| (err.line(), err.column()));
+                ParseError::new(err.to_string(), location)
+            }),
+            Some("toml") => toml::from_str(contents).map_err(|err| {
+                let location = err.span().map(|span| line_column(contents, span.start));
+                ParseError::new(err.message().to_string(), location)
+            }),
+            _ => serde_yaml::from_str(contents).map_err(|err| {
+                let location = err.location().map(|at| (at.line(), at.column()));
+                ParseError::new(err.to_string(), location)
+            }),
+        }
+    }
+
+    /// The minute chime as a repeat rule, when `minute_chime` is set. It
+    /// starts one interval in, so it never doubles up with the horn.
+    pub fn chime_rule(&self) -> Option<RepeatRule> {
+        let interval = self.chime_interval_secs.filter(|secs| *secs > 0).unwrap_or(60);
+        Some(RepeatRule {
+            start: interval,
+            interval,
+            end: None,
+            sound: self.minute_chime.clone()?,
+        })
+    }
+
+    /// The day/night sound as a repeat rule, when `day_night` is on and
+    /// `day_night_sound` set. The first turn is to night at 5:00.
+    pub fn day_night_rule(&self) -> Option<RepeatRule> {
+        if !self.day_night {
+            return None;
+        }
+        Some(RepeatRule {
+            start: DAY_NIGHT_SECS,
+            interval: DAY_NIGHT_SECS,
+            end: None,
+            sound: self.day_night_sound.clone()?,
+        })
+    }
+
+    /// The sound for the built-in warnings.
+    pub fn warning_sound(&self) -> String {
+        self.warning_sound
+            .clone()
+            .or_else(|| self.beep_sound.clone())
+            .unwrap_or_else(|| DEFAULT_BEEP_SOUND.to_string())
+    }
+
+    /// Joins every relative sound path to `dir`, the config file's folder, so
+    /// a folder of configs and sounds can be moved as a whole. The built-in
+    /// default sound names are still looked up in the working directory.
+    pub fn resolve_paths(&mut self, dir: &Path) {
+        let sets = std::iter::once((&mut self.audio, &mut self.repeat))
+            .chain(self.profiles.values_mut().map(|set| (&mut set.audio, &mut set.repeat)));
+        for (audio, repeat) in sets {
+            for spec in audio.values_mut() {
+                spec.resolve_paths(dir);
+            }
+            for rule in repeat {
+                resolve_path(dir, &mut rule.sound);
+            }
+        }
+        for event in &mut self.events {
+            event.entry.resolve_paths(dir);
+        }
+        let sounds = [
+            &mut self.pre_announce_sound,
+            &mut self.rosh_sound,
+            &mut self.cooldown_sound,
+            &mut self.fallback_sound,
+            &mut self.horn_sound,
+            &mut self.beep_sound,
+            &mut self.stack_sound,
+            &mut self.minute_chime,
+            &mut self.day_night_sound,
+            &mut self.warning_sound,
+            &mut self.ambience,
+            &mut self.bounty_rune_sound,
+            &mut self.power_rune_sound,
+            &mut self.wisdom_rune_sound,
+        ];
+        for path in sounds.into_iter().flatten() {
+            resolve_path(dir, path);
+        }
+        for spec in &mut self.quick_timers {
+            if let QuickTimerSpec::Timer(QuickTimer { sound: Some(sound), .. }) = spec {
+                resolve_path(dir, sound);
+            }
+        }
+    }
+
+    /// Layers `other`'s callouts over these, for running several files at
+    /// once. Its entries replace ours at the same second, its rune presets,
+    /// events, stacking reminder and chimes come along, and its
+    /// `lead_time_secs` and heads-up settings stay with its own callouts.
+    /// Every other setting is kept from `self`.
+    pub fn merge(&mut self, other: Config) {
+        let lead = other.lead_time_secs;
+        let pre_announce = other.pre_announce_secs;
+        let pre_sound = other.pre_announce_sound.clone();
+        let rules = other
+            .stack_rule()
+            .into_iter()
+            .chain(other.chime_rule())
+            .chain(other.day_night_rule());
+        let with_lead = |(time, spec): (TriggerTime, AudioSpec)| {
+            let mut entry = AudioEntry::from(spec);
+            entry.lead = entry.lead.or(Some(lead));
+            entry.pre_announce = entry.pre_announce.or(Some(pre_announce));
+            entry.pre_sound = entry.pre_sound.take().or_else(|| pre_sound.clone());
+            (time, AudioSpec::Entry(entry))
+        };
+
+        let presets = other
+            .rune_presets()
+            .into_iter()
+            .chain(other.event_entries())
+            .map(|(time, entry)| (time, AudioSpec::Entry(entry)));
+        self.audio.extend(presets.chain(other.audio).map(&with_lead));
+        self.repeat.extend(rules.chain(other.repeat));
+        for (name, set) in other.profiles {
+            let profile = self.profiles.entry(name).or_default();
+            profile.audio.extend(set.audio.into_iter().map(&with_lead));
+            profile.repeat.extend(set.repeat);
+        }
+    }
+
+    /// The stacking reminder as a repeat rule, when `stack_sound` is set.
+    pub fn stack_rule(&self) -> Option<RepeatRule> {
+        let minute = self.stack_from_minute.unwrap_or(0);
+        Some(RepeatRule {
+            start: minute.saturating_mul(60).saturating_add(STACK_SECOND),
+            interval: 60,
+            end: None,
+            sound: self.stack_sound.clone()?,
+        })
+    }
+
+    /// The rune callouts added by `rune_presets`, empty when it's off.
+    pub fn rune_presets(&self) -> Vec<(TriggerTime, AudioEntry)> {
+        if !self.rune_presets {
+            return Vec::new();
+        }
+
+        // (event, sound, default sound)
+        let runes = [
+            ("bounty_rune", &self.bounty_rune_sound, "./bounty_rune.wav"),
+            ("power_rune", &self.power_rune_sound, "./power_rune.wav"),
+            ("wisdom_rune", &self.wisdom_rune_sound, "./wisdom_rune.wav"),
+        ];
+        runes
+            .into_iter()
+            .filter_map(|(name, sound, default)| Some((known_event(name)?, sound, default)))
+            .flat_map(|(event, sound, default)| {
+                let label = event.1;
+                let entry = AudioEntry {
+                    file: Some(sound.clone().unwrap_or_else(|| default.to_string())),
+                    label: Some(label.to_string()),
+                    ..Default::default()
+                };
+                event_seconds(event)
+                    .into_iter()
+                    .map(move |second| (TriggerTime::from_secs(second), entry.clone()))
+            })
+            .collect()
+    }
+
+    /// The callouts of `events`, one at every time its event happens up to
+    /// 90:00. Unknown event names are left out with a warning.
+    pub fn event_entries(&self) -> Vec<(TriggerTime, AudioEntry)> {
+        let mut entries = Vec::new();
+        for event in &self.events {
+            let name = event.event.trim().to_ascii_lowercase();
+            let Some(schedule) = known_event(&name) else {
+                let known: Vec<&str> = EVENTS.iter().map(|(known, ..)| *known).collect();
+                eprintln!(
+                    "Ignoring unknown event {:?}; known events are {}",
+                    event.event,
+                    known.join(", ")
+                );
+                continue;
+            };
+            let mut entry = event.entry.clone();
+            entry.label = entry.label.or_else(|| Some(schedule.1.to_string()));
+            let times = event_seconds(schedule).into_iter().map(TriggerTime::from_secs);
+            entries.extend(times.map(|time| (time, entry.clone())));
+        }
+        entries
+    }
+
+    /// The well-formed `quick_timers` with their lengths. The rest are left
+    /// out with a warning.
+    pub fn quick_timers(&self) -> Vec<(QuickTimer, Duration)> {
+        let mut timers = Vec::new();
+        for (index, spec) in self.quick_timers.iter().enumerate() {
+            let QuickTimerSpec::Timer(timer) = spec else {
+                let number = index + 1;
+                eprintln!("Ignoring quick timer #{number}: it needs a `label` and a `duration`");
+                continue;
+            };
+            match timer.length() {
+                Some(length) => timers.push((timer.clone(), length)),
+                None => eprintln!(
+                    "Ignoring quick timer {:?}: duration must be seconds or m:ss, up to 2 hours",
+                    timer.label
+                ),
+            }
+        }
+        timers
+    }
+
+    /// When `auto_loop` starts the timer over, if it is on and has an end.
+    pub fn loop_end(&self) -> Option<Duration> {
+        if !self.auto_loop {
+            return None;
+        }
+        match self.end_secs {
+            Some(secs) if secs > 0 => Some(Duration::from_secs(secs.into())),
+            _ => {
+                eprintln!("Ignoring auto_loop: it needs an end_secs after the horn");
+                None
+            }
+        }
+    }
+
+    /// `default_volume` and `default_fade`, for filling in the callouts.
+    pub fn entry_defaults(&self) -> EntryDefaults {
+        EntryDefaults {
+            volume: self.default_volume.map(|volume| volume.clamp(0.0, 1.0)),
+            fade: self.default_fade,
+        }
+    }
+
+    /// The countdown beep's sound, when `countdown_beeps` is on.
+    pub fn beep_sound(&self) -> Option<String> {
+        self.countdown_beeps
+            .then(|| self.beep_sound.clone().unwrap_or_else(|| DEFAULT_BEEP_SOUND.to_string()))
+    }
+
+    /// Pre-game countdown length, falling back to the default when absent or
+    /// zero. Zero only when the countdown is skipped.
+    pub fn countdown(&self) -> Duration {
+        if self.skip_countdown {
+            return Duration::ZERO;
+        }
+        match self.countdown_secs {
+            Some(secs) if secs > 0 => Duration::from_secs(secs.into()),
+            _ => PREGAME_COUNTDOWN,
+        }
+    }
+}
+
+/// When a callout plays, in milliseconds on the game clock. Written in the
+/// config as whole or fractional seconds, e.g. `180` or `180.5`, or as the
+/// clock reads, e.g. `"3:00"` or `"-0:30"` for 30 seconds before the horn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TriggerTime(pub i64);
+
+impl TriggerTime {
+    /// Furthest a time may be from the horn, about 31 years.
+    const LIMIT_SECS: f64 = 1e9;
+
+    pub fn from_secs(secs: i32) -> Self {
+        Self(i64::from(secs) * 1000)
+    }
+
+    fn from_secs_f64<E: de::Error>(secs: f64) -> Result<Self, E> {
+        if !secs.is_finite() || secs.abs() > Self::LIMIT_SECS {
+            return Err(E::custom(format!("trigger time {secs} is out of range")));
+        }
+        Ok(Self((secs * 1000.0).round() as i64))
+    }
+}
+
+// Map keys arrive as numbers from YAML but as strings from JSON and TOML
+impl<'de> Deserialize<'de> for TriggerTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = TriggerTime;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a time in seconds or m:ss, such as 180, 180.5 or -0:30")
+            }
+
+            fn visit_i64<E: de::Error>(self, secs: i64) -> Result<TriggerTime, E> {
+                TriggerTime::from_secs_f64(secs as f64)
+            }
+
+            fn visit_u64<E: de::Error>(self, secs: u64) -> Result<TriggerTime, E> {
+                TriggerTime::from_secs_f64(secs as f64)
+            }
+
+            fn visit_f64<E: de::Error>(self, secs: f64) -> Result<TriggerTime, E> {
+                TriggerTime::from_secs_f64(secs)
+            }
+
+            fn visit_str<E: de::Error>(self, secs: &str) -> Result<TriggerTime, E> {
+                let input = secs.trim();
+                if let Ok(parsed) = input.parse::<f64>() {
+                    return TriggerTime::from_secs_f64(parsed);
+                }
+                let (sign, clock) = match input.strip_prefix('-') {
+                    Some(rest) => (-1.0, rest),
+                    None => (1.0, input),
+                };
+                let time = crate::parse_clock(clock)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(secs), &self))?;
+                TriggerTime::from_secs_f64(sign * time.as_secs_f64())
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Why a config file couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    NotFound(String),
+    Read(String, io::Error),
+    /// Not valid for its format, or not in the shape of a config.
+    Parse(String, ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotFound(file) => write!(f, "{file} not found"),
+            LoadError::Read(file, err) => write!(f, "Cannot read {file}: {err}"),
+            LoadError::Parse(file, err) => write!(f, "Cannot parse {file}: {err}"),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// A config that doesn't parse, with where in the file the problem is when
+/// the format tells.
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    /// Line and column, counting from 1.
+    pub location: Option<(usize, usize)>,
+}
+
+impl ParseError {
+    /// Drops the location from `message`, where serde_json and serde_yaml
+    /// write it out, so it isn't given twice.
+    fn new(message: String, location: Option<(usize, usize)>) -> Self {
+        let message = match location {
+            Some((line, column)) => {
+                message.replacen(&format!(" at line {line} column {column}"), "", 1)
+            }
+            None => message,
+        };
+        Self { message: message.trim_end().to_string(), location }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((line, column)) = self.location {
+            write!(f, "line {line}, column {column}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// The line and column, counting from 1, of byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+/// A discovered config file: loaded by its full path, but shown in the
+/// picker by file name only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFile(pub String);
+
+impl fmt::Display for ConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Path::new(&self.0).file_name().map(|name| name.to_string_lossy());
+        f.write_str(name.as_deref().unwrap_or(&self.0))
+    }
+}
+
+pub fn is_config_file(path: &Path) -> bool {
+    extension(path).is_some_and(|ext| CONFIG_EXTENSIONS.contains(&ext.as_str()))
+}
+
+pub fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_ascii_lowercase())
+}
+
+/// Joins a relative `path` to `dir`. A file that only exists relative to the
+/// working directory, where configs used to be resolved, is left alone so
+/// older configs keep working.
+pub fn resolve_path(dir: &Path, path: &mut String) {
+    let original = Path::new(path.as_str());
+    let joined = dir.join(original);
+    if original.is_absolute() || (!joined.exists() && original.exists()) {
+        return;
+    }
+    *path = joined.to_string_lossy().into_owned();
+}
+
+/// One named strategy inside a multi-profile config.
+#[derive(Debug, Default, Deserialize)]
+pub struct AudioSet {
+    #[serde(default)]
+    pub audio: HashMap<TriggerTime, AudioSpec>,
+    #[serde(default)]
+    pub repeat: Vec<RepeatRule>,
+}
+
+/// A single `audio` entry as written in the config: either the bare
+/// `second: path` shorthand, a list of paths played in order, or a map with
+/// the full set of fields.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum AudioSpec {
+    Path(String),
+    Sequence(Vec<String>),
+    Entry(AudioEntry),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AudioEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// More sound files played one after another, following `file` if set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Text spoken through text-to-speech when no `file` is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub say: Option<String>,
+    /// Human-readable name shown in the UI instead of the file name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Loudness of just this callout (0.0–1.0), scaled by the master volume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    /// Fade lengths in milliseconds, overriding the config-wide ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade_in_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade_out_ms: Option<u32>,
+    /// Seconds early to play this callout, overriding `lead_time_secs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lead: Option<u16>,
+    /// Boost or cut in decibels, for evening out files of different loudness.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gain_db: Option<f32>,
+    /// Seconds ahead of this callout to give a heads-up, overriding
+    /// `pre_announce_secs`; 0 turns it off for this entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_announce: Option<u16>,
+    /// Played for this callout's heads-up instead of `pre_announce_sound`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sound: Option<String>,
+    /// Playback speed, where above 1.0 plays higher and shorter (1.0 by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    /// Stereo placement from -1.0 (left) to 1.0 (right), centered by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pan: Option<f32>,
+    /// Repeat the sound until it is dismissed, for alerts that mustn't be missed.
+    #[serde(default, rename = "loop", skip_serializing_if = "is_false")]
+    pub looping: bool,
+    /// Set to false to keep the entry in the file without it playing.
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+impl AudioSpec {
+    fn resolve_paths(&mut self, dir: &Path) {
+        match self {
+            AudioSpec::Path(file) => resolve_path(dir, file),
+            AudioSpec::Sequence(files) => files.iter_mut().for_each(|file| resolve_path(dir, file)),
+            AudioSpec::Entry(entry) => entry.resolve_paths(dir),
+        }
+    }
+}
+
+/// What a callout gets for the fields it leaves unset; see `Config::entry_defaults`.
+#[derive(Debug, Clone, Copy)]
+pub struct EntryDefaults {
+    volume: Option<f32>,
+    fade: Option<u32>,
+}
+
+impl EntryDefaults {
+    /// Fills in what `entry` leaves unset, so `audio_map` holds each callout's
+    /// effective volume and fades.
+    pub fn apply(self, entry: &mut AudioEntry) {
+        entry.volume = entry.volume.or(self.volume);
+        entry.fade_in_ms = entry.fade_in_ms.or(self.fade);
+        entry.fade_out_ms = entry.fade_out_ms.or(self.fade);
+    }
+}
+
+/// An `events` entry: the event's name alongside the usual entry fields.
+#[derive(Debug, Deserialize)]
+pub struct EventEntry {
+    pub event: String,
+    #[serde(flatten)]
+    pub entry: AudioEntry,
+}
+
+/// An enabled entry with nothing to play, for filling in the fields a
+/// literal leaves out.
+impl Default for AudioEntry {
+    fn default() -> Self {
+        AudioEntry {
+            file: None,
+            files: Vec::new(),
+            say: None,
+            label: None,
+            volume: None,
+            fade_in_ms: None,
+            fade_out_ms: None,
+            lead: None,
+            gain_db: None,
+            pre_announce: None,
+            pre_sound: None,
+            speed: None,
+            pan: None,
+            looping: false,
+            enabled: true,
+        }
+    }
+}
+
+impl AudioEntry {
+    /// The label if one was given, otherwise the sound's file name or spoken text.
+    pub fn name(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        match (self.sounds().next(), &self.say) {
+            (Some(file), _) => Path::new(file).file_stem().map_or_else(
+                || file.to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            ),
+            (None, Some(text)) => text.clone(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// The sound files to play, in order.
+    pub fn sounds(&self) -> impl Iterator<Item = &str> {
+        self.file.iter().chain(&self.files).map(String::as_str)
+    }
+
+    pub fn has_sound(&self) -> bool {
+        self.sounds().next().is_some() || self.say.is_some()
+    }
+
+    fn resolve_paths(&mut self, dir: &Path) {
+        let files = self.file.iter_mut().chain(&mut self.files).chain(&mut self.pre_sound);
+        files.for_each(|file| resolve_path(dir, file));
+    }
+}
+
+impl From<AudioSpec> for AudioEntry {
+    fn from(spec: AudioSpec) -> Self {
+        match spec {
+            AudioSpec::Path(file) => AudioEntry { file: Some(file), ..Default::default() },
+            AudioSpec::Sequence(files) => AudioEntry { files, ..Default::default() },
+            AudioSpec::Entry(entry) => entry,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_sounds_are_resolved_once_whatever_the_profiles() {
+        let yaml = "events:\n  - event: tormentor\n    file: horn.wav\n\
+                    profiles:\n  safe: {}\n  greedy: {}\n";
+        let mut config = Config::parse("test.yaml", yaml).unwrap();
+        config.resolve_paths(Path::new("configs"));
+        let expected = Path::new("configs").join("horn.wav");
+        assert_eq!(config.events[0].entry.file.as_deref(), expected.to_str());
+    }
+
+    #[test]
+    fn warnings_and_rune_presets_follow_the_event_schedule() {
+        let rules: Vec<(u16, u16)> = RepeatRule::warnings("beep.wav")
+            .iter()
+            .map(|rule| (rule.start, rule.interval))
+            .collect();
+        assert_eq!(rules, [(170, 180), (350, 120), (410, 420), (290, 300)]);
+
+        let config = Config::parse("test.yaml", "rune_presets: true\n").unwrap();
+        let presets = config.rune_presets();
+        let power: Vec<TriggerTime> = presets
+            .iter()
+            .filter(|(_, entry)| entry.label.as_deref() == Some("Power rune"))
+            .map(|(time, _)| *time)
+            .take(2)
+            .collect();
+        assert_eq!(power, [TriggerTime::from_secs(360), TriggerTime::from_secs(480)]);
+        let last = presets.iter().map(|(time, _)| *time).max();
+        assert_eq!(last, Some(TriggerTime::from_secs(90 * 60)));
+    }
+
+    #[test]
+    fn each_load_failure_says_what_went_wrong() {
+        let dir = std::env::temp_dir().join(format!("dota-timer-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let missing = dir.join("missing.yaml");
+        let missing = missing.to_str().unwrap();
+        assert!(matches!(Config::load(missing), Err(LoadError::NotFound(file)) if file == missing));
+        assert_eq!(Config::load(missing).unwrap_err().to_string(), format!("{missing} not found"));
+
+        // A folder exists but can't be read as a file
+        let folder = dir.to_str().unwrap();
+        assert!(matches!(Config::load(folder), Err(LoadError::Read(..))));
+
+        let broken = dir.join("broken.yaml");
+        fs::write(&broken, "audio:\n  60: ./rune.wav\n  - oops\n").unwrap();
+        let broken = broken.to_str().unwrap();
+        match Config::load(broken) {
+            Err(LoadError::Parse(file, err)) => {
+                assert_eq!(file, broken);
+                assert_eq!(err.location.map(|(line, _)| line), Some(3));
+                assert!(!err.message.contains("line 3"), "location given once: {err}");
+            }
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{
+    audio,
+    config::{self, AudioEntry, Config, RepeatRule, TriggerTime},
+};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_yaml::{Mapping, Value};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Sound library folder, next to the config, when `sound_library` isn't set.
+const DEFAULT_LIBRARY: &str = "sounds";
+
+/// How deep into the library's subfolders sounds are looked for.
+const LIBRARY_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub enum EditorAction {
+    Toggle,
+    TimeChanged(String),
+    FileChanged(String),
+    LabelChanged(String),
+    QueryChanged(String),
+    /// Plays a library sound, given by its path in the config.
+    Preview(String),
+    /// Puts a library sound into the file input.
+    Pick(String),
+    Add,
+    Remove,
+    Save,
+    SaveAsChanged(String),
+    /// Writes everything loaded to the new file named in the Save as input.
+    SaveAs,
+}
+
+/// In-app panel for adding and removing `audio` entries of the selected
+/// file. Changes apply to the running timer right away and are written back
+/// on save.
+#[derive(Debug, Default)]
+pub struct Editor {
+    pub open: bool,
+    /// Game time as `m:ss`, `-m:ss` or whole seconds.
+    pub time: String,
+    pub file: String,
+    pub label: String,
+    /// Filters the sound library by file name.
+    pub query: String,
+    /// File name for Save as, in the working directory.
+    pub save_as: String,
+    /// Folder of the selected config, which sound paths are relative to.
+    pub dir: PathBuf,
+    // Sounds found in the library, as paths relative to `dir`
+    library: Vec<String>,
+    // Unsaved changes by game second; `None` removes the entry there
+    edits: BTreeMap<i32, Option<AudioEntry>>,
+}
+
+impl Editor {
+    /// The game second typed in, if it parses.
+    pub fn second(&self) -> Option<i32> {
+        let input = self.time.trim();
+        let (negative, input) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let secs = match crate::parse_clock(input) {
+            Some(time) => i32::try_from(time.as_secs()).ok()?,
+            None => input.parse::<u32>().ok().and_then(|secs| i32::try_from(secs).ok())?,
+        };
+        Some(if negative { -secs } else { secs })
+    }
+
+    /// The entry described by the inputs, once its sound checks out.
+    pub fn entry(&self) -> Result<AudioEntry, String> {
+        let file = self.file.trim();
+        if file.is_empty() {
+            return Err("Enter a sound file".to_string());
+        }
+        audio::check_playable(&self.resolve_path(file))
+            .map_err(|err| format!("Cannot play {file}: {err}"))?;
+        let label = self.label.trim();
+        Ok(AudioEntry {
+            file: Some(file.to_string()),
+            label: (!label.is_empty()).then(|| label.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// `path` as written in the config, resolved the way the config's are.
+    pub fn resolve_path(&self, path: &str) -> String {
+        let mut path = path.to_string();
+        config::resolve_path(&self.dir, &mut path);
+        path
+    }
+
+    /// `entry` with its sound resolved for playing. The unresolved one is
+    /// what gets saved.
+    pub fn resolve(&self, entry: &AudioEntry) -> AudioEntry {
+        let mut entry = entry.clone();
+        entry.file = entry.file.map(|file| self.resolve_path(&file));
+        entry
+    }
+
+    /// Lists the sounds in `folder` (relative to the config, `sounds` when
+    /// `None`) and its subfolders.
+    pub fn scan_library(&mut self, folder: Option<&str>) {
+        let folder = folder.unwrap_or(DEFAULT_LIBRARY);
+        self.library.clear();
+        scan(&self.dir.join(folder), folder, LIBRARY_DEPTH, &mut self.library);
+        self.library.sort();
+    }
+
+    /// Library sounds whose path contains the query, ignoring case.
+    pub fn library_matches(&self) -> impl Iterator<Item = &str> {
+        let query = self.query.trim().to_lowercase();
+        self.library
+            .iter()
+            .map(String::as_str)
+            .filter(move |path| path.to_lowercase().contains(&query))
+    }
+
+    /// Records an added (`Some`) or removed (`None`) entry to save later.
+    pub fn set(&mut self, second: i32, entry: Option<AudioEntry>) {
+        self.edits.insert(second, entry);
+    }
+
+    pub fn has_edits(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    pub fn discard(&mut self) {
+        self.edits.clear();
+    }
+
+    /// Replays the unsaved changes onto a freshly loaded config's callouts,
+    /// keyed by the game millisecond they're written at, so a reload doesn't
+    /// lose them.
+    pub fn apply(&self, entries: &mut BTreeMap<i64, AudioEntry>) {
+        for (second, entry) in &self.edits {
+            match entry {
+                Some(entry) => entries.insert(i64::from(*second) * 1000, self.resolve(entry)),
+                None => entries.remove(&(i64::from(*second) * 1000)),
+            };
+        }
+    }
+
+    /// Writes the unsaved changes into the `audio` section of the YAML file
+    /// at `path`. The rest of the file is kept, though comments are lost.
+    pub fn save(&mut self, path: &str) -> Result<(), String> {
+        if !matches!(config::extension(Path::new(path)).as_deref(), Some("yaml" | "yml")) {
+            return Err("Only YAML files can be saved from the editor".to_string());
+        }
+
+        let contents = fs::read_to_string(path).map_err(|err| format!("Cannot read {path}: {err}"))?;
+        let mut doc: Value =
+            serde_yaml::from_str(&contents).map_err(|err| format!("Cannot parse {path}: {err}"))?;
+        if doc.is_null() {
+            doc = Value::Mapping(Mapping::new());
+        }
+        let audio = doc
+            .as_mapping_mut()
+            .ok_or_else(|| format!("{path} is not a YAML mapping"))?
+            .entry(Value::from("audio"))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        if audio.is_null() {
+            *audio = Value::Mapping(Mapping::new());
+        }
+        let audio = audio
+            .as_mapping_mut()
+            .ok_or_else(|| format!("`audio` in {path} is not a mapping"))?;
+
+        // Keys may be written as `3:00`, `-0:30` or `180.0` for the same time,
+        // so the one already there is found by what it means
+        for (second, entry) in &self.edits {
+            let millis = i64::from(*second) * 1000;
+            let existing = audio
+                .keys()
+                .find(|key| TriggerTime::deserialize(*key).is_ok_and(|time| time.0 == millis))
+                .cloned();
+            match (entry, existing) {
+                (Some(entry), Some(key)) => {
+                    audio.insert(key, entry_value(entry));
+                }
+                (Some(entry), None) => {
+                    audio.insert(Value::from(*second), entry_value(entry));
+                }
+                (None, Some(key)) => {
+                    audio.shift_remove(&key);
+                }
+                (None, None) => {}
+            }
+        }
+
+        // Never write out something the timer couldn't load back
+        let output = serde_yaml::to_string(&doc).map_err(|err| err.to_string())?;
+        Config::parse(path, &output).map_err(|err| format!("Edited config would not load: {err}"))?;
+        fs::write(path, output).map_err(|err| format!("Cannot write {path}: {err}"))?;
+        self.edits.clear();
+        Ok(())
+    }
+}
+
+/// The config-wide settings Save as keeps along with the callouts, since
+/// they change how each one plays.
+#[derive(Debug, Default, Serialize)]
+pub struct ExportSettings {
+    #[serde(skip_serializing_if = "is_zero")]
+    pub pre_announce_secs: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_announce_sound: Option<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub fade_in_ms: u32,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub fade_out_ms: u32,
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Writes the loaded callouts, by game millisecond, repeat rules and
+/// `settings` to a new YAML config named `name` in the working directory,
+/// and returns its path. Sounds are written as resolved, which the working
+/// directory's configs load as they are.
+pub fn export(
+    name: &str,
+    audio_map: &BTreeMap<i64, AudioEntry>,
+    repeat: &[RepeatRule],
+    settings: &ExportSettings,
+) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Enter a file name to save as".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("Save as takes a file name, not a folder".to_string());
+    }
+    let path = Path::new(".").join(name);
+    let path = match config::extension(&path).as_deref() {
+        Some("yaml" | "yml") => path,
+        Some(_) => return Err("Save as only writes YAML files".to_string()),
+        None => path.with_extension("yaml"),
+    };
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    let path = path.to_string_lossy().into_owned();
+
+    // The keys are when each callout plays, so any lead is already taken off
+    let audio = audio_map
+        .iter()
+        .map(|(millis, entry)| (*millis, AudioEntry { lead: None, ..entry.clone() }))
+        .collect();
+    let doc = Export { settings, audio: Callouts(&audio), repeat };
+
+    let output = serde_yaml::to_string(&doc).map_err(|err| err.to_string())?;
+    Config::parse(&path, &output).map_err(|err| format!("Saved config would not load: {err}"))?;
+    fs::write(&path, output).map_err(|err| format!("Cannot write {path}: {err}"))?;
+    Ok(path)
+}
+
+/// What Save as writes: the settings, the callouts and the repeat rules, if any.
+#[derive(Serialize)]
+struct Export<'a> {
+    #[serde(flatten)]
+    settings: &'a ExportSettings,
+    audio: Callouts<'a>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    repeat: &'a [RepeatRule],
+}
+
+/// Callouts keyed by game second, with an entry that is nothing but a `file`
+/// written as the bare path.
+struct Callouts<'a>(&'a BTreeMap<i64, AudioEntry>);
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Time {
+    Whole(i64),
+    Fraction(f64),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Callout<'a> {
+    Path(&'a str),
+    Entry(&'a AudioEntry),
+}
+
+impl Serialize for Callouts<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(millis, entry)| {
+            let time = if millis % 1000 == 0 {
+                Time::Whole(millis / 1000)
+            } else {
+                Time::Fraction(*millis as f64 / 1000.0)
+            };
+            let file_only = serde_yaml::to_value(entry)
+                .is_ok_and(|value| value.as_mapping().is_some_and(|map| map.len() == 1));
+            let callout = match &entry.file {
+                Some(file) if file_only => Callout::Path(file),
+                _ => Callout::Entry(entry),
+            };
+            (time, callout)
+        }))
+    }
+}
+
+/// Adds the supported sounds under `dir` to `found`, written as `prefix/name`
+/// with forward slashes so the config reads the same on every platform. A
+/// missing folder just has no sounds.
+fn scan(dir: &Path, prefix: &str, depth: usize, found: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            if depth > 0 {
+                scan(&path, &name, depth - 1, found);
+            }
+        } else if audio::is_supported(&path) {
+            found.push(name);
+        }
+    }
+}
+
+/// A bare path when there's no label, matching the usual shorthand.
+fn entry_value(entry: &AudioEntry) -> Value {
+    let file = Value::from(entry.file.clone().unwrap_or_default());
+    match &entry.label {
+        Some(label) => {
+            let mut map = Mapping::new();
+            map.insert(Value::from("file"), file);
+            map.insert(Value::from("label"), Value::from(label.as_str()));
+            Value::Mapping(map)
+        }
+        None => file,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(file: &str) -> AudioEntry {
+        AudioEntry::from(config::AudioSpec::Path(file.to_string()))
+    }
+
+    #[test]
+    fn save_finds_keys_written_as_the_clock_reads() {
+        let path = std::env::temp_dir().join(format!("dota-timer-save-{}.yaml", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        fs::write(&path, "audio:\n  \"3:00\": ./rune.wav\n  \"-0:30\": ./early.wav\n  60: ./one.wav\n")
+            .unwrap();
+
+        let mut editor = Editor::default();
+        editor.set(180, None);
+        editor.set(-30, Some(entry("./late.wav")));
+        let result = editor.save(&path);
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        let config = Config::parse(&path, &saved).unwrap();
+        let mut times: Vec<i64> = config.audio.keys().map(|time| time.0).collect();
+        times.sort();
+        assert_eq!(times, [-30_000, 60_000]);
+        assert!(saved.contains("-0:30"), "the replaced entry keeps its key: {saved}");
+        assert!(saved.contains("./late.wav"));
+    }
+
+    #[test]
+    fn exports_keep_the_heads_up_and_fade_settings() {
+        let audio = BTreeMap::from([(60_000, entry("./rune.wav"))]);
+        let settings = ExportSettings {
+            pre_announce_secs: 15,
+            pre_announce_sound: Some("./soon.wav".to_string()),
+            fade_in_ms: 200,
+            fade_out_ms: 0,
+        };
+        let doc = Export { settings: &settings, audio: Callouts(&audio), repeat: &[] };
+        let output = serde_yaml::to_string(&doc).unwrap();
+
+        let config = Config::parse("export.yaml", &output).unwrap();
+        assert_eq!(config.pre_announce_secs, 15);
+        assert_eq!(config.pre_announce_sound.as_deref(), Some("./soon.wav"));
+        assert_eq!((config.fade_in_ms, config.fade_out_ms), (200, 0));
+        assert!(!output.contains("fade_out_ms"), "unset settings are left out: {output}");
+    }
+}
@@ -0,0 +1,133 @@
+use global_hotkey::{hotkey::HotKey, GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use iced::futures::{channel::mpsc, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use std::fmt;
+
+const DEFAULT_START_RESTART: &str = "Ctrl+Alt+S";
+const DEFAULT_PAUSE_RESUME: &str = "Ctrl+Alt+P";
+
+/// Optional `hotkeys` section of a strategy config overriding the default combos.
+#[derive(Debug, Default, Deserialize)]
+pub struct HotkeyConfig {
+    #[serde(default)]
+    pub start_restart: Option<String>,
+    #[serde(default)]
+    pub pause_resume: Option<String>,
+    /// Speaks the game time; unbound unless set.
+    #[serde(default)]
+    pub announce_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    StartRestart,
+    PauseResume,
+    AnnounceTime,
+}
+
+/// System-wide shortcuts that keep working while the game has keyboard focus.
+pub struct GlobalHotkeys {
+    manager: Option<GlobalHotKeyManager>,
+    bindings: Vec<(HotKey, HotkeyAction)>,
+}
+
+impl GlobalHotkeys {
+    pub fn new() -> Self {
+        let manager = GlobalHotKeyManager::new()
+            .map_err(|err| eprintln!("Global hotkeys unavailable: {err}"))
+            .ok();
+        let mut hotkeys = Self {
+            manager,
+            bindings: Vec::new(),
+        };
+        hotkeys.rebind(&HotkeyConfig::default());
+        hotkeys
+    }
+
+    /// None registered, and none ever will be.
+    pub fn disabled() -> Self {
+        Self {
+            manager: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Replaces the registered combos with the ones in `config`, falling back
+    /// to the defaults for anything it leaves out. Combos that fail to parse or
+    /// register (e.g. another app owns them) are logged and skipped.
+    pub fn rebind(&mut self, config: &HotkeyConfig) {
+        let Some(manager) = &self.manager else {
+            return;
+        };
+
+        for (hotkey, _) in self.bindings.drain(..) {
+            let _ = manager.unregister(hotkey);
+        }
+
+        let wanted = [
+            (
+                config.start_restart.as_deref().unwrap_or(DEFAULT_START_RESTART),
+                HotkeyAction::StartRestart,
+            ),
+            (
+                config.pause_resume.as_deref().unwrap_or(DEFAULT_PAUSE_RESUME),
+                HotkeyAction::PauseResume,
+            ),
+        ];
+        let optional = config
+            .announce_time
+            .as_deref()
+            .map(|combo| (combo, HotkeyAction::AnnounceTime));
+        for (combo, action) in wanted.into_iter().chain(optional) {
+            let hotkey = match combo.parse::<HotKey>() {
+                Ok(hotkey) => hotkey,
+                Err(err) => {
+                    eprintln!("Invalid hotkey {combo:?}: {err}");
+                    continue;
+                }
+            };
+            match manager.register(hotkey) {
+                Ok(()) => self.bindings.push((hotkey, action)),
+                Err(err) => eprintln!("Failed to register hotkey {combo:?}: {err}"),
+            }
+        }
+    }
+
+    pub fn action(&self, id: u32) -> Option<HotkeyAction> {
+        self.bindings
+            .iter()
+            .find(|(hotkey, _)| hotkey.id() == id)
+            .map(|(_, action)| *action)
+    }
+}
+
+impl Default for GlobalHotkeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for GlobalHotkeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalHotkeys")
+            .field("available", &self.manager.is_some())
+            .field("bindings", &self.bindings)
+            .finish()
+    }
+}
+
+/// Stream of pressed hotkey ids, for use with `Subscription::run`.
+pub fn events() -> impl Stream<Item = u32> {
+    iced::stream::channel(16, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state() == HotKeyState::Pressed {
+                let _ = sender.unbounded_send(event.id());
+            }
+        }));
+
+        while let Some(id) = receiver.next().await {
+            let _ = output.send(id).await;
+        }
+    })
+}
@@ -0,0 +1,127 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    process,
+};
+
+/// Lock file in the temp directory holding the running instance's process id.
+const LOCK_FILE: &str = "dota2-timer.lock";
+
+/// Keeps a second copy of the timer with the same `--title` from starting
+/// and doubling every callout, while differently titled timers, e.g. one
+/// per scrim, run side by side. The lock file is removed when this is dropped.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Takes the lock for `title`, or explains which process already holds
+    /// it. A lock left behind by a crashed instance is reclaimed.
+    pub fn acquire(title: Option<&str>) -> Result<Self, String> {
+        let path = std::env::temp_dir().join(lock_file(title));
+
+        // Two tries: the second follows removing a stale lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    if let Err(err) = write!(file, "{}", process::id()) {
+                        eprintln!("Failed to write {}: {err}", path.display());
+                    }
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|contents| contents.trim().parse::<u32>().ok());
+                    if let Some(pid) = holder.filter(|pid| is_running(*pid)) {
+                        let title = title.map(|title| format!(" as {title:?}")).unwrap_or_default();
+                        return Err(format!("Dota Timer is already running{title} (process {pid})"));
+                    }
+                    // Left over from a crash; the file is ours to replace
+                    fs::remove_file(&path).map_err(|err| {
+                        format!("Cannot remove stale lock {}: {err}", path.display())
+                    })?;
+                }
+                // Better to run unguarded than not at all
+                Err(err) => {
+                    eprintln!(
+                        "Cannot create {}, skipping the single-instance check: {err}",
+                        path.display()
+                    );
+                    return Ok(Self { path: PathBuf::new() });
+                }
+            }
+        }
+        Err(format!("Cannot take the lock {}", path.display()))
+    }
+}
+
+/// The untitled timer keeps the plain name; a title's characters that can't
+/// go in a file name are replaced.
+fn lock_file(title: Option<&str>) -> String {
+    match title {
+        None => LOCK_FILE.to_string(),
+        Some(title) => {
+            let name: String = title
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect();
+            format!("dota2-timer-{name}.lock")
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, STILL_ACTIVE},
+        System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+
+    // SAFETY: the handle is checked before use and closed once read
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+/// `kill -0` only checks that the process exists, without signalling it.
+#[cfg(not(windows))]
+fn is_running(pid: u32) -> bool {
+    // Our own id means the old holder is gone and the id got reused by us
+    pid != process::id()
+        && process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titles_get_their_own_lock() {
+        assert_eq!(lock_file(None), LOCK_FILE);
+        assert_eq!(lock_file(Some("Scrim A")), "dota2-timer-Scrim_A.lock");
+        assert_ne!(lock_file(Some("Scrim A")), lock_file(Some("Scrim B")));
+        assert_eq!(lock_file(Some("../x")), "dota2-timer-___x.lock");
+    }
+}
@@ -1,38 +1,297 @@
+mod audio;
+mod cli;
+mod clipboard;
+mod config;
+mod editor;
+mod hotkeys;
+mod instance;
+mod overlay;
+mod recovery;
+mod saved_state;
+mod theme;
+mod timeline;
+mod tray;
+mod trigger_log;
+
+use audio::{AudioPlayer, Fade};
+use clipboard::Clipboard;
+use config::{
+    AudioEntry, Config, ConfigFile, RepeatRule, DAY_NIGHT_SECS, DEFAULT_BEEP_SOUND, PREGAME_COUNTDOWN,
+    WARNINGS, WARNING_LEAD_SECS,
+};
+use directories::ProjectDirs;
+use editor::{Editor, EditorAction, ExportSettings};
+use hotkeys::{GlobalHotkeys, HotkeyAction, HotkeyConfig};
+use instance::InstanceLock;
+use overlay::Overlay;
+use recovery::{Phase, Recovery};
+use saved_state::SavedState;
+use theme::AppTheme;
+use timeline::Timeline;
+use tray::{Tray, TrayAction};
+use trigger_log::TriggerLog;
 use iced::{
-    time, widget, Size,
+    application::{Appearance, DefaultStyle},
+    keyboard::{self, key, Key},
+    event, time, widget, Color, Event, Point, Size, Task, Theme,
     window::{self, Level},
     Subscription,
 };
-//use iced::executor::Default;
-use std::default::Default;
-use rodio::{Decoder, OutputStream, Sink};
-use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     fs,
-    time::{Duration, Instant},
+    ops::{Range, RangeBounds},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
 use std::collections::HashSet;
-//use iced::window::Position::Default;
 
-#[derive(Debug, Deserialize)]
-struct Config {
-    audio: HashMap<u16, String>,
-}
+/// How many upcoming callouts the panel lists at once.
+const UPCOMING_LIMIT: usize = 10;
+/// How often a game in progress is checkpointed for crash recovery.
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(5);
+/// How long the "reloaded" indicator stays up after a hot reload.
+const RELOAD_FLASH: Duration = Duration::from_secs(2);
+/// How long the Restart button waits for its confirming second click.
+const RESTART_CONFIRM: Duration = Duration::from_secs(2);
+/// Size range of the time display, and how much the A-/A+ buttons change it.
+const DEFAULT_FONT_SIZE: f32 = 25.0;
+const MIN_FONT_SIZE: f32 = 12.0;
+const MAX_FONT_SIZE: f32 = 96.0;
+const FONT_SIZE_STEP: f32 = 4.0;
+/// Size of the next event's countdown, relative to the time display.
+const NEXT_EVENT_SCALE: f32 = 0.7;
+/// Range of `ui_scale`, which multiplies the system's own display scaling.
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.0;
+/// Lowest window opacity; anything fainter makes the clock unreadable.
+const MIN_OPACITY: f32 = 0.1;
+/// How often the clock updates while running, and the range `tick_ms` may set.
+/// Triggers are worked out from the elapsed time, so a slow tick only delays them.
+const DEFAULT_TICK: Duration = Duration::from_millis(100);
+const MIN_TICK_MS: u64 = 10;
+const MAX_TICK_MS: u64 = 1000;
+/// Clock speeds offered for fast-forwarding through a config, and the range
+/// `--speed` may set.
+const SPEEDS: [f32; 4] = [1.0, 10.0, 30.0, 60.0];
+const MIN_SPEED: f32 = 0.1;
+const MAX_SPEED: f32 = 100.0;
+/// How long the time display stays tinted after a callout plays.
+const FLASH_DURATION: Duration = Duration::from_millis(500);
+const DEFAULT_FLASH_COLOR: Color = Color::from_rgb(1.0, 0.75, 0.0);
+/// Colors of the day/night indicator.
+const DAY_COLOR: Color = Color::from_rgb(0.95, 0.7, 0.2);
+const NIGHT_COLOR: Color = Color::from_rgb(0.45, 0.6, 1.0);
+
+/// Upcoming callouts switched off with `enabled: false` or their checkbox.
+const DISABLED_COLOR: Color = Color::from_rgb(0.5, 0.5, 0.5);
+/// Device picker entry that goes back to the system's default output.
+const DEFAULT_AUDIO_DEVICE: &str = "System default";
+const DEFAULT_TITLE: &str = "Dota Timer";
+/// One-tap cooldown timers offered below the Roshan timer, unless the config
+/// has its own `quick_timers`.
+const COOLDOWN_PRESETS: [(&str, Duration); 4] = [
+    ("Glyph", Duration::from_secs(5 * 60)),
+    ("Scan", Duration::from_secs(4 * 60 + 30)),
+    ("Buyback", Duration::from_secs(8 * 60)),
+    ("Smoke", Duration::from_secs(7 * 60)),
+];
+/// Game seconds that get a countdown beep: 3, 2 and 1 before the horn.
+const BEEP_SECONDS: Range<i64> = -3..0;
+/// Most sound library entries listed at once; a search narrows down the rest.
+const LIBRARY_LIMIT: usize = 100;
+/// Longest heads-up `pre_announce_secs` may give before a callout.
+const MAX_PRE_ANNOUNCE_SECS: u16 = 120;
+/// How late a trigger may still play when ticks fall behind, and the most
+/// `late_grace_secs` may allow.
+const DEFAULT_LATE_GRACE: Duration = Duration::from_secs(10);
+const MAX_LATE_GRACE_SECS: u16 = 120;
+/// Ambience level under the master volume when `ambience_volume` isn't set.
+const DEFAULT_AMBIENCE_VOLUME: f32 = 0.3;
+/// How long "Copied" shows after the game time was put on the clipboard.
+const COPIED_NOTICE: Duration = Duration::from_millis(1500);
+/// Most clock nudges that can be undone, newest first.
+const MAX_UNDO: usize = 20;
+/// Earliest and latest Roshan respawn after a kill.
+const ROSH_MIN_RESPAWN: Duration = Duration::from_secs(8 * 60);
+const ROSH_MAX_RESPAWN: Duration = Duration::from_secs(11 * 60);
 
 #[derive(Debug)]
 struct TimerApp {
     state: TimerState,
-    yaml_files: Vec<String>,
+    yaml_files: Vec<ConfigFile>,
     selected_file: Option<String>,
-    audio_map: HashMap<Duration, String>,
+    // The file Page Up/Page Down have stepped to, loaded when Enter is pressed
+    highlighted_file: Option<String>,
+    // Extra files layered over the selected one, in the order added; later
+    // ones win when two set a callout at the same second
+    merged_files: Vec<String>,
+    // Profile names in the selected file, sorted; empty for a single-strategy file
+    profiles: Vec<String>,
+    selected_profile: Option<String>,
+    // Keyed by game-clock millisecond: 0 is the horn, negative is during the
+    // countdown. Sorted so the upcoming callouts can be read straight off the front
+    audio_map: BTreeMap<i64, AudioEntry>,
+    countdown: Duration,
     current_display: Duration,
-    triggered_audio: HashSet<Duration>,
+    repeat_rules: Vec<RepeatRule>,
+    // Scheduled triggers that have already played (or been skipped over)
+    fired: HashSet<Trigger>,
+    // Last game millisecond checked for triggers, so a tick that skips past one still fires it
+    checked_through: Option<i64>,
+    tick: Duration,
+    // Game seconds per real second; above 1.0 to simulate a config quickly
+    speed: f32,
+    rosh_state: Option<RoshTimer>,
+    rosh_sound: Option<String>,
+    cooldowns: Vec<Cooldown>,
+    cooldown_presets: Vec<CooldownPreset>,
+    cooldown_sound: Option<String>,
+    horn_sound: Option<String>,
+    // Played over the last seconds of the countdown; `None` when beeps are off
+    beep_sound: Option<String>,
+    // Heads-up ahead of each callout, for entries that don't set their own
+    pre_announce_secs: u16,
+    pre_announce_sound: Option<String>,
+    trigger_log: TriggerLog,
+    audio: AudioPlayer,
+    // Device picker options, with the system default first
+    audio_devices: Vec<String>,
+    volume: f32,
+    muted: bool,
+    held_keys: HashSet<Key>,
+    hotkeys: GlobalHotkeys,
+    overlay: Overlay,
+    // Kept alive so the icon stays in the tray
+    _tray: Tray,
+    opacity: f32,
+    always_on_top: bool,
+    config_error: Option<String>,
+    // Whether the selected file loaded, so an empty one can be told from a broken one
+    config_loaded: bool,
+    last_callout: Option<String>,
+    // Tint for the time display when a callout plays; `None` turns it off
+    flash_color: Option<Color>,
+    flashed_at: Option<Instant>,
+    // Show the time of day while idle or paused
+    wall_clock: bool,
+    // Show whether it is day or night in the game
+    day_night: bool,
+    // Which of the built-in `WARNINGS` are ticked, and their rules in the same order
+    warnings: [bool; WARNINGS.len()],
+    warning_rules: Vec<RepeatRule>,
+    // Background track looped while the game clock runs, and its level
+    ambience: Option<String>,
+    ambience_volume: f32,
+    config_modified: Option<SystemTime>,
+    // Checkpoint left by a run that didn't exit cleanly, offered for resuming
+    recovery: Option<Recovery>,
+    reloaded_at: Option<Instant>,
+    // When Restart was first clicked mid-game, waiting for a second click
+    restart_armed: Option<Instant>,
+    // Clock nudges since the start, oldest first, for undoing
+    adjustments: Vec<Adjustment>,
+    // The game time last copied to the clipboard, or why it couldn't be, and when
+    copied: Option<(Result<String, String>, Instant)>,
+    clipboard: Clipboard,
+    // Window title from `--title`, and the loaded config's, which wins
+    title: Option<String>,
+    config_title: Option<String>,
+    theme: AppTheme,
+    font_size: f32,
+    ui_scale: f32,
+    window_size: Option<Size>,
+    // Clock-only view, and the window size to go back to when leaving it
+    compact: bool,
+    expanded_size: Option<Size>,
+    window_position: Option<Point>,
+    editor: Editor,
+    // The callouts and repeat rules as loaded, before the sounds that won't play
+    // were dropped, so Save as writes them all
+    loaded_audio: BTreeMap<i64, AudioEntry>,
+    loaded_repeat: Vec<RepeatRule>,
+    // Callout picked on the timeline, for the Test button and the editor
+    selected_callout: Option<i64>,
+    // The game millisecond each `audio_map` callout is written at in the config,
+    // by when it plays; the two differ by its lead time
+    written_at: BTreeMap<i64, i64>,
+    // Game time typed in to sync the clock to, as m:ss
+    sync_input: String,
+    // Narrows the file selector down to names containing it
+    file_filter: String,
+    game_clock: bool,
+    pause_on_unfocus: bool,
+    // How late a trigger may still play after the clock stalled
+    late_grace: Duration,
+    // Game time at which `auto_loop` starts the timer over
+    loop_end: Option<Duration>,
+    /// Keeps the game's callouts and their heads-ups from playing during the
+    /// countdown; see `Config::running_only`.
+    running_only: bool,
+    sound_library: Option<String>,
+    // Paused by losing focus rather than by hand, so regaining it resumes
+    auto_paused: bool,
+}
+
+/// Roshan respawn window, counted from the kill. It pauses along with the
+/// game clock, since the game's own pause stops Roshan too.
+#[derive(Debug)]
+struct RoshTimer {
+    // Time counted up to the last pause, and when it resumed (`None` while paused)
+    base: Duration,
+    resumed_at: Option<Instant>,
+    elapsed: Duration,
+    min_alert_played: bool,
+}
+
+impl RoshTimer {
+    fn pause(&mut self, now: Instant) {
+        if let Some(resumed_at) = self.resumed_at.take() {
+            self.base += now.duration_since(resumed_at);
+            self.elapsed = self.base;
+        }
+    }
+
+    fn resume(&mut self, now: Instant) {
+        self.resumed_at.get_or_insert(now);
+    }
+}
+
+/// A button for starting a cooldown, built in or from `quick_timers`.
+#[derive(Debug)]
+struct CooldownPreset {
+    label: String,
+    length: Duration,
+    // Played when it runs out, instead of `cooldown_sound`
+    sound: Option<String>,
+}
 
+fn default_cooldown_presets() -> Vec<CooldownPreset> {
+    COOLDOWN_PRESETS
+        .iter()
+        .map(|(label, length)| CooldownPreset {
+            label: label.to_string(),
+            length: *length,
+            sound: None,
+        })
+        .collect()
 }
 
+/// A glyph, buyback or similar cooldown started from the preset buttons.
+/// Like the Roshan timer, it freezes while the game clock is paused.
 #[derive(Debug)]
+struct Cooldown {
+    label: String,
+    remaining: Duration,
+    sound: Option<String>,
+    // Last tick it counted down from; `None` while paused or once it ran out
+    last_tick: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
 enum TimerState {
+    #[default]
     Idle,
     CountingDown(Instant),
     Running {
@@ -42,222 +301,3009 @@ enum TimerState {
     Paused(Duration),
 }
 
-impl Default for TimerState {
-    fn default() -> Self {
-        TimerState::Idle
+/// One scheduled occurrence of a callout, so each plays at most once per pass
+/// of the clock no matter how many ticks land on its second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Trigger {
+    /// The `audio_map` entry at this game millisecond.
+    Callout(i64),
+    /// An occurrence of the repeat rule at this index.
+    Repeat { rule: usize, second: u64 },
+    /// An occurrence of the built-in warning at this index of `WARNINGS`.
+    Warning { warning: usize, second: u64 },
+    /// The `horn_sound` at the end of the countdown.
+    Horn,
+    /// A `beep_sound` in the last seconds of the countdown, at this game second.
+    Beep(i64),
+    /// The heads-up at game millisecond `at` for the callout at `callout`.
+    PreAnnounce { at: i64, callout: i64 },
+}
+
+impl Trigger {
+    fn game_millis(self) -> i64 {
+        match self {
+            Trigger::Callout(millis) => millis,
+            Trigger::Repeat { second, .. } | Trigger::Warning { second, .. } => second as i64 * 1000,
+            Trigger::Horn => 0,
+            Trigger::Beep(second) => second * 1000,
+            Trigger::PreAnnounce { at, .. } => at,
+        }
     }
 }
 
+/// A nudge of the clock, kept so it can be undone.
+#[derive(Debug)]
+struct Adjustment {
+    /// How far the clock actually moved, in game milliseconds.
+    millis: i64,
+    /// Triggers it skipped over, which undoing it lets play again.
+    skipped: HashSet<Trigger>,
+}
+
 #[derive(Debug, Clone)]
 enum Message {
     StartRestart,
+    // The Start/Restart button, shortcut, hotkey or tray item, which ask again mid-game
+    RestartPressed,
+    ApplyConfigNow,
     PauseResume,
     LoadYaml(String),
+    // Loads the config file this many places down the selector, wrapping around
+    CycleConfig(isize),
+    MergeYaml(String),
+    ClearMerged,
+    SelectProfile(String),
     Tick(Instant),
+    VolumeChanged(f32),
+    ToggleMute,
+    // Stops the looping alert
+    DismissAlert,
+    RoshKilled,
+    RoshClear,
+    StartCooldown(usize),
+    RemoveCooldown(usize),
+    KeyPressed(Key),
+    KeyReleased(Key),
+    GlobalHotkey(u32),
+    Tray(TrayAction),
+    SetOpacity(f32),
+    ToggleAlwaysOnTop,
+    /// Ticks or unticks the built-in warning at this index of `WARNINGS`.
+    ToggleWarning(usize),
+    ToggleCompact,
+    CheckConfig,
+    ConfigReloaded,
+    AdjustTime(i64),
+    UndoAdjust,
+    // Puts the game time on the clipboard, for pasting into chat
+    CopyTime,
+    StepToNext,
+    SetTheme(AppTheme),
+    SetSpeed(f32),
+    SetFontSize(f32),
+    WindowMoved(Point),
+    WindowFocused(bool),
+    WindowResized(Size),
+    TestSound(i64),
+    /// Switches the callout at this game millisecond on or off until the next load.
+    ToggleCallout(i64),
+    SelectCallout(i64),
+    SelectAudioDevice(String),
+    SyncInputChanged(String),
+    FilterChanged(String),
+    Editor(EditorAction),
+    SyncTo(Duration),
+    CloseRequested(window::Id),
+    SaveRecovery,
+    ResumeRecovery,
+    DismissRecovery,
 }
 
-impl Default for TimerApp {
-    fn default() -> Self {
+impl TimerApp {
+    /// A stopped timer with nothing loaded that reaches nothing outside
+    /// itself: no saved state, config folders, recovery file, audio output,
+    /// hotkeys or tray. `Default` is this with all of them.
+    fn new() -> Self {
         Self {
-            yaml_files: get_yaml_files(),
+            yaml_files: Vec::new(),
             selected_file: None,
-            audio_map: HashMap::new(),
+            highlighted_file: None,
+            merged_files: Vec::new(),
+            profiles: Vec::new(),
+            selected_profile: None,
+            audio_map: BTreeMap::new(),
+            countdown: PREGAME_COUNTDOWN,
             state: TimerState::default(),
-            current_display: Duration::ZERO,
-            triggered_audio: HashSet::new(),
+            // Idle shows the countdown that Start will begin
+            current_display: PREGAME_COUNTDOWN,
+            repeat_rules: Vec::new(),
+            fired: HashSet::new(),
+            checked_through: None,
+            tick: DEFAULT_TICK,
+            speed: 1.0,
+            rosh_state: None,
+            rosh_sound: None,
+            cooldowns: Vec::new(),
+            cooldown_presets: default_cooldown_presets(),
+            cooldown_sound: None,
+            horn_sound: None,
+            beep_sound: None,
+            pre_announce_secs: 0,
+            pre_announce_sound: None,
+            trigger_log: TriggerLog::default(),
+            audio: AudioPlayer::offline(),
+            audio_devices: vec![DEFAULT_AUDIO_DEVICE.to_string()],
+            volume: 1.0,
+            muted: false,
+            held_keys: HashSet::new(),
+            hotkeys: GlobalHotkeys::disabled(),
+            overlay: Overlay::default(),
+            _tray: Tray::none(),
+            opacity: 1.0,
+            always_on_top: false,
+            config_error: None,
+            config_loaded: false,
+            last_callout: None,
+            flash_color: Some(DEFAULT_FLASH_COLOR),
+            flashed_at: None,
+            wall_clock: false,
+            day_night: false,
+            warnings: [false; WARNINGS.len()],
+            warning_rules: RepeatRule::warnings(DEFAULT_BEEP_SOUND),
+            ambience: None,
+            ambience_volume: DEFAULT_AMBIENCE_VOLUME,
+            config_modified: None,
+            recovery: None,
+            reloaded_at: None,
+            restart_armed: None,
+            adjustments: Vec::new(),
+            copied: None,
+            clipboard: Clipboard::default(),
+            title: None,
+            config_title: None,
+            theme: AppTheme::default(),
+            font_size: DEFAULT_FONT_SIZE,
+            ui_scale: 1.0,
+            window_size: None,
+            compact: false,
+            expanded_size: None,
+            window_position: None,
+            editor: Editor::default(),
+            loaded_audio: BTreeMap::new(),
+            loaded_repeat: Vec::new(),
+            selected_callout: None,
+            written_at: BTreeMap::new(),
+            sync_input: String::new(),
+            file_filter: String::new(),
+            game_clock: false,
+            pause_on_unfocus: false,
+            late_grace: DEFAULT_LATE_GRACE,
+            loop_end: None,
+            running_only: true,
+            sound_library: None,
+            auto_paused: false,
+        }
+    }
+}
+
+impl Default for TimerApp {
+    fn default() -> Self {
+        let saved = SavedState::load();
+        let mut app = Self {
+            yaml_files: get_yaml_files(&config_dirs()).into_iter().map(ConfigFile).collect(),
+            audio: AudioPlayer::new(saved.audio_device.clone()),
+            audio_devices: std::iter::once(DEFAULT_AUDIO_DEVICE.to_string())
+                .chain(audio::output_devices())
+                .collect(),
+            hotkeys: GlobalHotkeys::new(),
+            _tray: Tray::new(),
+            always_on_top: saved.always_on_top,
+            warnings: WARNINGS.map(|(name, ..)| saved.warnings.iter().any(|saved| saved == name)),
+            recovery: Recovery::load(),
+            window_size: saved.window_size.map(|(width, height)| Size::new(width, height)),
+            compact: saved.compact,
+            window_position: saved.window_position.map(|(x, y)| Point::new(x, y)),
+            ..Self::new()
+        };
+
+        if app.compact {
+            app.expanded_size = app.window_size;
+        }
+
+        // Restore the last strategy so Start works right away
+        if let Some(file) = saved.selected_file {
+            if Path::new(&file).is_file() {
+                app.load_file(file);
+            }
         }
+        app
     }
 }
 
-fn get_yaml_files() -> Vec<String> {
-    fs::read_dir(".")
-        .unwrap()
+/// The folders configs are picked up from: the working directory, its
+/// `configs/` folder and the user's config directory.
+fn config_dirs() -> Vec<PathBuf> {
+    let user_dir = ProjectDirs::from("", "", "dota2-timer").map(|dirs| dirs.config_dir().to_owned());
+    [PathBuf::from("."), PathBuf::from("configs")].into_iter().chain(user_dir).collect()
+}
+
+/// Config files in `dirs`, without duplicates. Folders that don't exist or
+/// can't be read are skipped.
+fn get_yaml_files(dirs: &[PathBuf]) -> Vec<String> {
+    // A missing folder is normal; anything else is worth a note
+    let entries = dirs.iter().filter_map(|dir| match fs::read_dir(dir) {
+        Ok(entries) => Some(entries),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => {
+            eprintln!("Cannot read {}: {err}", dir.display());
+            None
+        }
+    });
+
+    let mut seen = HashSet::new();
+    entries
+        .flatten()
         .filter_map(|entry| {
             let path = entry.ok()?.path();
-            if path.extension()? == "yaml" {
-                Some(path.to_string_lossy().into_owned())
+            if config::is_config_file(&path) {
+                Some(path)
             } else {
                 None
             }
         })
+        // The user directory may well be the working directory too
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .map(|path| path.to_string_lossy().into_owned())
         .collect()
 }
 
 impl TimerApp {
-    fn check_audio_triggers(&mut self) {
-        let current_sec = self.current_display.as_secs();
-        let trigger_point = Duration::from_secs(current_sec);
+    fn load_file(&mut self, file: String) {
+        self.audio_map.clear();  // Clear previous entries
+        self.editor.discard();
+        self.loaded_audio.clear();
+        self.loaded_repeat.clear();
+        self.selected_callout = None;
+        self.written_at.clear();
+        self.profiles.clear();
+        self.repeat_rules.clear();
+        self.rosh_sound = None;
+        self.cooldown_sound = None;
+        self.cooldown_presets = default_cooldown_presets();
+        self.horn_sound = None;
+        self.beep_sound = None;
+        self.pre_announce_secs = 0;
+        self.pre_announce_sound = None;
+        self.trigger_log = TriggerLog::default();
+        self.audio.set_serialized(false);
+        self.audio.set_normalize(false);
+        self.audio.set_fade(Fade::default());
+        self.audio.set_fallback(None);
+        self.flash_color = Some(DEFAULT_FLASH_COLOR);
+        self.config_title = None;
+        self.pause_on_unfocus = false;
+        self.late_grace = DEFAULT_LATE_GRACE;
+        self.loop_end = None;
+        self.running_only = true;
+        self.day_night = false;
+        self.warning_rules = RepeatRule::warnings(DEFAULT_BEEP_SOUND);
+        self.ambience = None;
+        self.ambience_volume = DEFAULT_AMBIENCE_VOLUME;
+        self.audio.stop_ambience();
+        self.sound_library = None;
+        self.editor.dir = Path::new(&file).parent().map(Path::to_path_buf).unwrap_or_default();
+        self.audio.preload([]);
+        self.hotkeys.rebind(&HotkeyConfig::default());
+        self.config_error = None;
+        self.config_loaded = false;
+        self.fired.clear();
+        self.checked_through = None;
+        self.tick = DEFAULT_TICK;
+        self.last_callout = None;
+        self.countdown = PREGAME_COUNTDOWN;
+        if matches!(self.state, TimerState::Idle) {
+            self.current_display = self.countdown;
+        }
+        self.selected_file = Some(file);
+        self.highlighted_file = None;
+        self.config_modified = self.sources_modified();
 
-        if self.audio_map.contains_key(&trigger_point)
-            && !self.triggered_audio.contains(&trigger_point)
-        {
-            if let Some(path) = self.audio_map.get(&trigger_point) {
-                play_audio(path);
-                self.triggered_audio.insert(trigger_point);
-            }
+        match self.read_config() {
+            Ok(config) => self.apply_config(config),
+            Err(err) => self.config_error = Some(err),
         }
     }
-}
 
-fn play_audio(path: &str) {
-    let path = path.to_string();
-    std::thread::spawn(move || {
-        let (_stream, handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&handle).unwrap();
-        let file = fs::File::open(&path).unwrap();
-        let source = Decoder::new(file).unwrap();
-        sink.append(source);
-        sink.sleep_until_end();
-    });
-}
+    /// The selected file with every merged file layered over it.
+    fn read_config(&self) -> Result<Config, String> {
+        let Some(file) = &self.selected_file else {
+            return Err("No file selected".to_string());
+        };
+        let mut config = Config::load(file).map_err(|err| err.to_string())?;
+        for file in &self.merged_files {
+            config.merge(Config::load(file).map_err(|err| err.to_string())?);
+        }
+        Ok(config)
+    }
 
-fn main() -> iced::Result {
-    iced::application("Dota Timer", update, view)
-        .subscription(subscription)
-        .window(window::Settings {
-            level: Level::AlwaysOnTop,
-            size: Size::new(200.0, 120.0),
-            ..window::Settings::default()
-        })
-        .run()
-}
+    /// The latest modification time among the selected and merged files.
+    fn sources_modified(&self) -> Option<SystemTime> {
+        self.selected_file
+            .iter()
+            .chain(&self.merged_files)
+            .filter_map(|file| modified_time(file))
+            .max()
+    }
 
-fn subscription(state: &TimerApp) -> Subscription<Message> {
-    match &state.state {
-        TimerState::CountingDown(_) | TimerState::Running{ .. } => {
-            time::every(Duration::from_millis(10)).map(Message::Tick)
+    /// Re-reads the selected file after it changed on disk. Triggers that
+    /// already passed stay fired; on failure the previous config is kept.
+    fn reload_config(&mut self) -> Result<(), String> {
+        if self.selected_file.is_none() {
+            return Ok(());
         }
-        _ => Subscription::none(),
+        let config = self.read_config().map_err(|err| format!("Reload failed: {err}"))?;
+
+        self.apply_config(config);
+        let passed = self.game_millis().unwrap_or(i64::MIN);
+        self.fired.retain(|trigger| trigger.game_millis() <= passed);
+        Ok(())
     }
-}
 
-// UPDATE FUNCTION
-fn update(state: &mut TimerApp, message: Message) {
-    match message {
-        Message::StartRestart => {
-            // Always reset to initial state when clicking Start/Restart
-            state.state = TimerState::CountingDown(Instant::now());
-            state.current_display = Duration::from_secs(90);
-            state.triggered_audio.clear();
-
-            // Reload the selected YAML file if present
-            if let Some(file) = &state.selected_file {
-                if let Ok(contents) = fs::read_to_string(file) {
-                    let config: Config = serde_yaml::from_str(&contents).unwrap();
-                    state.audio_map = config
-                        .audio
-                        .into_iter()
-                        .map(|(k, v)| (Duration::from_secs(k.into()), v))
-                        .collect();
-                }
+    /// Nudges the clock by `secs` (negative moves it back) to sync with the
+    /// real game, remembering the nudge for `undo_adjust`.
+    fn adjust_time(&mut self, secs: i64) {
+        let (Some(before), fired) = (self.game_millis(), self.fired.clone()) else {
+            return;
+        };
+        self.shift_clock(secs * 1000);
+        let moved = self.game_millis().unwrap_or(before) - before;
+        let skipped = self.fired.difference(&fired).copied().collect();
+        self.adjustments.push(Adjustment { millis: moved, skipped });
+        if self.adjustments.len() > MAX_UNDO {
+            self.adjustments.remove(0);
+        }
+    }
+
+    /// Takes back the last nudge. What it skipped over may play again, but
+    /// what has played since stays played.
+    fn undo_adjust(&mut self) {
+        let Some(adjustment) = self.adjustments.pop() else {
+            return;
+        };
+        let played: Vec<Trigger> = self.fired.difference(&adjustment.skipped).copied().collect();
+        self.shift_clock(-adjustment.millis);
+        self.fired.extend(played);
+    }
+
+    /// Moves the clock by `millis` and re-derives what counts as played. The
+    /// game clock never goes below zero, and the countdown never goes back
+    /// past its full length.
+    fn shift_clock(&mut self, millis: i64) {
+        let delta = Duration::from_millis(millis.unsigned_abs());
+        let shift = |time: Duration| {
+            if millis >= 0 {
+                time + delta
+            } else {
+                time.saturating_sub(delta)
             }
-        },
-        Message::PauseResume => match &state.state {
+        };
+
+        let now = Instant::now();
+        match self.state {
+            TimerState::CountingDown(start_time) => {
+                // A later game time means the countdown started earlier
+                let elapsed = shift(self.game_since(start_time)).min(self.countdown);
+                self.state = TimerState::CountingDown(self.start_before(now, elapsed));
+                self.current_display = round_up_secs(self.countdown - elapsed);
+            },
             TimerState::Running { base_time, last_start } => {
-                let elapsed = *base_time + last_start.elapsed();
-                state.state = TimerState::Paused(elapsed);
-                state.current_display = elapsed;
+                let base_time = shift(base_time + self.game_since(last_start));
+                self.state = TimerState::Running { base_time, last_start: now };
+                self.current_display = base_time;
             },
             TimerState::Paused(elapsed) => {
-                state.state = TimerState::Running {
-                    base_time: *elapsed,
-                    last_start: Instant::now(),
-                };
+                self.state = TimerState::Paused(shift(elapsed));
+                self.current_display = shift(elapsed);
             },
-            _ => {}
-        },
-        Message::LoadYaml(file) => {
-            state.selected_file = Some(file.clone());
-            state.audio_map.clear();  // Clear previous entries
-            state.triggered_audio.clear();
-
-            if let Ok(contents) = fs::read_to_string(&file) {
-                if let Ok(config) = serde_yaml::from_str::<Config>(&contents) {
-                    state.audio_map = config.audio.into_iter().map(|(k, v)| {
-                        (Duration::from_secs(k.into()), v)
-                    }).collect();
-                }
+            TimerState::Idle => return,
+        }
+        // Callouts in the second landed on still play
+        if let Some(now) = self.game_second() {
+            self.rebuild_triggered(now * 1000);
+        }
+    }
+
+    /// Sets the clock to game millisecond `millis`, running or paused, with
+    /// everything up to it counted as played.
+    fn jump_to(&mut self, millis: i64, paused: bool) {
+        let now = Instant::now();
+        let time = Duration::from_millis(millis.unsigned_abs());
+        if paused {
+            self.state = TimerState::Paused(time);
+            self.current_display = time;
+        } else if millis < 0 {
+            let remaining = time.min(self.countdown);
+            let start = self.start_before(now, self.countdown - remaining);
+            self.state = TimerState::CountingDown(start);
+            self.current_display = round_up_secs(remaining);
+        } else {
+            self.state = TimerState::Running { base_time: time, last_start: now };
+            self.current_display = time;
+        }
+        self.last_callout = None;
+        self.rebuild_triggered(millis + 1);
+    }
+
+    /// Re-derives which triggers count as fired after a jump of the clock:
+    /// everything before game millisecond `before`, so jumping forward doesn't
+    /// dump the skipped callouts and jumping back lets them play again.
+    fn rebuild_triggered(&mut self, before: i64) {
+        self.checked_through = Some(before - 1);
+        self.fired = self.triggers_before(before);
+    }
+
+    /// Every trigger strictly before game millisecond `before`.
+    fn triggers_before(&self, before: i64) -> HashSet<Trigger> {
+        let callouts = self.audio_map.range(..before).map(|(millis, _)| Trigger::Callout(*millis));
+        // Whole-second triggers strictly before `before`
+        let before_secs = ceil_secs(before);
+        let beeps = (BEEP_SECONDS.start..before_secs.min(BEEP_SECONDS.end)).map(Trigger::Beep);
+        // Repeats only run after the horn, so there's nothing to skip before it
+        let before_secs = u64::try_from(before_secs).unwrap_or(0);
+        let repeats = self.repeat_rules.iter().enumerate().flat_map(|(rule, repeat)| {
+            repeat
+                .occurrences_before(before_secs)
+                .map(move |second| Trigger::Repeat { rule, second })
+        });
+        let warnings = self.warning_rules.iter().enumerate().flat_map(|(warning, rule)| {
+            rule.occurrences_before(before_secs)
+                .map(move |second| Trigger::Warning { warning, second })
+        });
+        let horn = (before > 0).then_some(Trigger::Horn);
+        // Heads-ups already due, including those of callouts still ahead
+        let pre_announces: Vec<Trigger> = self
+            .pre_announces(..before.saturating_add(i64::from(MAX_PRE_ANNOUNCE_SECS) * 1000))
+            .filter(|trigger| trigger.game_millis() < before)
+            .collect();
+        callouts.chain(repeats).chain(warnings).chain(horn).chain(beeps).chain(pre_announces).collect()
+    }
+
+    /// Whether a callout is written at game millisecond `millis` in the
+    /// config, whenever its lead time makes it play.
+    fn is_written_at(&self, millis: i64) -> bool {
+        self.written_at.values().any(|written| *written == millis)
+    }
+
+    /// The current whole second on the game clock: counted from the horn,
+    /// negative during the countdown. `None` while idle.
+    fn game_second(&self) -> Option<i64> {
+        // The countdown display is rounded up, so it never reads 0 before the horn
+        let secs = self.current_display.as_secs() as i64;
+        match self.state {
+            TimerState::Idle => None,
+            TimerState::CountingDown(_) => Some(-secs),
+            TimerState::Running { .. } | TimerState::Paused(_) => Some(secs),
+        }
+    }
+
+    /// The exact game-clock time in milliseconds, counted like `game_second`.
+    fn game_millis(&self) -> Option<i64> {
+        self.game_millis_at(Instant::now())
+    }
+
+    /// The game clock as it reads at `now`. A `now` before the clock started
+    /// reads as no time passed.
+    fn game_millis_at(&self, now: Instant) -> Option<i64> {
+        let millis = |time: Duration| time.as_millis() as i64;
+        let since = |instant: Instant| now.saturating_duration_since(instant).mul_f32(self.speed);
+        match &self.state {
+            TimerState::Idle => None,
+            TimerState::CountingDown(start_time) => {
+                Some(-millis(self.countdown.saturating_sub(since(*start_time))))
             }
-        },
-        Message::Tick(now) => match &mut state.state {
+            TimerState::Running { base_time, last_start } => {
+                Some(millis(*base_time + since(*last_start)))
+            }
+            TimerState::Paused(elapsed) => Some(millis(*elapsed)),
+        }
+    }
+
+    /// Whether a second click on Restart now would confirm it.
+    fn restart_confirming(&self) -> bool {
+        self.restart_armed.is_some_and(|at| at.elapsed() < RESTART_CONFIRM)
+    }
+
+    /// Game time passed since `instant`, at the clock's speed.
+    fn game_since(&self, instant: Instant) -> Duration {
+        instant.elapsed().mul_f32(self.speed)
+    }
+
+    /// Backdates a clock start so `game` time has passed since it at the
+    /// clock's speed.
+    fn start_before(&self, now: Instant, game: Duration) -> Instant {
+        now.checked_sub(game.div_f32(self.speed)).unwrap_or(now)
+    }
+
+    /// Moves the start of the countdown or game clock `game` time earlier,
+    /// so a start made late still keeps to schedule.
+    fn backdate(&mut self, game: Duration) {
+        let wall = game.div_f32(self.speed);
+        let earlier = |instant: Instant| instant.checked_sub(wall).unwrap_or(instant);
+        match &mut self.state {
+            TimerState::CountingDown(start_time) => *start_time = earlier(*start_time),
+            TimerState::Running { last_start, .. } => *last_start = earlier(*last_start),
+            TimerState::Paused(_) | TimerState::Idle => {}
+        }
+    }
+
+    /// Changes the clock speed, carrying on from the current game time.
+    fn set_speed(&mut self, speed: f32) {
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        let now = Instant::now();
+        match self.state {
             TimerState::CountingDown(start_time) => {
-                let remaining = Duration::from_secs(90).saturating_sub(now.duration_since(*start_time));
-                state.current_display = remaining;
+                let elapsed = self.game_since(start_time);
+                self.speed = speed;
+                self.state = TimerState::CountingDown(self.start_before(now, elapsed));
+            }
+            TimerState::Running { base_time, last_start } => {
+                let base_time = base_time + self.game_since(last_start);
+                self.state = TimerState::Running { base_time, last_start: now };
+            }
+            TimerState::Idle | TimerState::Paused(_) => {}
+        }
+        self.speed = speed;
+    }
 
-                if remaining.is_zero() {
-                    state.state = TimerState::Running {
-                        base_time: Duration::ZERO,
-                        last_start: Instant::now(),
-                    };
+    fn apply_config(&mut self, mut config: Config) {
+        self.config_loaded = true;
+        self.countdown = config.countdown();
+        if matches!(self.state, TimerState::Idle) {
+            self.current_display = self.countdown;
+        }
+        let stack_rule = config.stack_rule();
+        let chime_rule = config.chime_rule();
+        let day_night_rule = config.day_night_rule();
+        let presets = config.rune_presets();
+        let events = config.event_entries();
+        let beep_sound = config.beep_sound();
+        let warning_sound = config.warning_sound();
+        let quick_timers = config.quick_timers();
+        let loop_end = config.loop_end();
+        let entry_defaults = config.entry_defaults();
+
+        // Keep the chosen profile if the file still has it, otherwise take the first
+        self.profiles = config.profiles.keys().cloned().collect();
+        self.profiles.sort();
+        self.selected_profile = self
+            .selected_profile
+            .take()
+            .filter(|name| self.profiles.contains(name))
+            .or_else(|| self.profiles.first().cloned());
+        let profile = self
+            .selected_profile
+            .as_ref()
+            .and_then(|name| config.profiles.remove(name))
+            .unwrap_or_default();
+
+        // Explicit entries come last so they replace a preset or event at the same
+        // second, and the profile's replace the shared ones
+        let mut entries: BTreeMap<i64, AudioEntry> = presets
+            .into_iter()
+            .chain(events)
+            .chain(config.audio.into_iter().map(|(k, v)| (k, AudioEntry::from(v))))
+            .chain(profile.audio.into_iter().map(|(k, v)| (k, AudioEntry::from(v))))
+            .map(|(k, mut v)| {
+                entry_defaults.apply(&mut v);
+                (k.0, v)
+            })
+            .filter(|(millis, entry)| {
+                if !entry.has_sound() {
+                    eprintln!(
+                        "Ignoring audio entry at {}s with neither `file` nor `say`",
+                        format_millis(*millis)
+                    );
                 }
-            },
-            TimerState::Running { base_time, last_start } => {
-                let elapsed = *base_time + last_start.elapsed();
-                state.current_display = elapsed;
-                state.check_audio_triggers();
-            },
-            TimerState::Paused(elapsed) => {
-                state.current_display = *elapsed;
-            },
-            _ => {}
-        },
+                entry.has_sound()
+            })
+            .collect();
+
+        // Keyed by when each callout plays, which is earlier than its event by
+        // its lead time. A lead never pushes one before the countdown starts,
+        // nor one of the game's own before the horn with `running_only`
+        let running_only = config.running_only.unwrap_or(true);
+        let countdown_start = -(self.countdown.as_millis() as i64);
+        // Unsaved edits are by the time typed in, so they get the same lead
+        self.editor.apply(&mut entries);
+        self.audio_map = BTreeMap::new();
+        self.written_at.clear();
+        for (millis, entry) in entries {
+            let lead = i64::from(entry.lead.unwrap_or(config.lead_time_secs)) * 1000;
+            let earliest = if running_only && millis >= 0 { 0 } else { countdown_start };
+            let fire = (millis - lead).max(earliest.min(millis));
+            if let Some(replaced) = self.audio_map.get(&fire) {
+                eprintln!(
+                    "{} and {} both play at {}s after their lead times; keeping {}",
+                    replaced.name(),
+                    entry.name(),
+                    format_millis(fire),
+                    entry.name()
+                );
+            }
+            self.audio_map.insert(fire, entry);
+            self.written_at.insert(fire, millis);
+        }
+        self.repeat_rules = config
+            .repeat
+            .into_iter()
+            .chain(profile.repeat)
+            .filter(|rule| {
+                let valid = rule.is_valid();
+                if !valid {
+                    eprintln!("Ignoring invalid repeat rule: {rule:?}");
+                }
+                valid
+            })
+            .chain(stack_rule)
+            .chain(chime_rule)
+            .chain(day_night_rule)
+            .collect();
+        self.rosh_sound = config.rosh_sound;
+        self.cooldown_sound = config.cooldown_sound;
+        self.cooldown_presets = if quick_timers.is_empty() {
+            default_cooldown_presets()
+        } else {
+            quick_timers
+                .into_iter()
+                .map(|(timer, length)| CooldownPreset {
+                    label: timer.label,
+                    length,
+                    sound: timer.sound,
+                })
+                .collect()
+        };
+        self.beep_sound = beep_sound;
+        self.pre_announce_secs = config.pre_announce_secs;
+        self.pre_announce_sound = config.pre_announce_sound;
+        self.horn_sound = config.horn_sound;
+        self.ambience = config.ambience;
+        self.ambience_volume = config
+            .ambience_volume
+            .filter(|volume| volume.is_finite())
+            .map_or(DEFAULT_AMBIENCE_VOLUME, |volume| volume.clamp(0.0, 1.0));
+        self.trigger_log = TriggerLog::new(config.log_file);
+        self.audio.set_serialized(config.serialize_audio);
+        self.audio.set_normalize(config.normalize);
+        self.audio.set_fade(Fade {
+            fade_in: audio::millis(config.fade_in_ms),
+            fade_out: audio::millis(config.fade_out_ms),
+        });
+        self.hotkeys.rebind(&config.hotkeys);
+        self.audio.set_fallback(config.fallback_sound);
+        self.loaded_audio = self.audio_map.clone();
+        self.loaded_repeat = self.repeat_rules.clone();
+        self.drop_unplayable_sounds();
+        // Decode everything up front so no callout waits on the disk
+        let files = self
+            .audio_map
+            .values()
+            .flat_map(|entry| entry.sounds().chain(entry.pre_sound.as_deref()))
+            .chain(self.repeat_rules.iter().map(|rule| rule.sound.as_str()))
+            .chain(self.rosh_sound.as_deref())
+            .chain(self.cooldown_sound.as_deref())
+            .chain(self.cooldown_presets.iter().filter_map(|preset| preset.sound.as_deref()))
+            .chain(self.horn_sound.as_deref())
+            .chain(self.beep_sound.as_deref())
+            .chain(self.pre_announce_sound.as_deref());
+        self.audio.preload(files);
+        if let Some(opacity) = config.opacity {
+            self.opacity = opacity.clamp(MIN_OPACITY, 1.0);
+        }
+        if let Some(theme) = config.theme {
+            self.theme = theme;
+        }
+        self.config_title = config.title.filter(|title| !title.trim().is_empty());
+        self.pause_on_unfocus = config.pause_on_unfocus;
+        self.loop_end = loop_end;
+        self.late_grace = config.late_grace_secs.map_or(DEFAULT_LATE_GRACE, |secs| {
+            Duration::from_secs(secs.clamp(1, MAX_LATE_GRACE_SECS).into())
+        });
+        self.running_only = config.running_only.unwrap_or(true);
+        self.day_night = config.day_night;
+        self.warning_rules = RepeatRule::warnings(&warning_sound);
+        self.sound_library = config.sound_library;
+        if let Some(size) = config.font_size {
+            self.font_size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        }
+        if let Some(scale) = config.ui_scale {
+            self.ui_scale = scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+        }
+        if let Some(game_clock) = config.game_clock {
+            self.game_clock = game_clock;
+        }
+        if let Some(wall_clock) = config.wall_clock {
+            self.wall_clock = wall_clock;
+        }
+        let default_tick = if config.precise_timing {
+            Duration::from_millis(MIN_TICK_MS)
+        } else {
+            DEFAULT_TICK
+        };
+        self.tick = config.tick_ms.map_or(default_tick, |ms| {
+            Duration::from_millis(ms.clamp(MIN_TICK_MS, MAX_TICK_MS))
+        });
+        self.flash_color = match (config.flash, config.flash_color) {
+            (Some(false), _) => None,
+            (_, Some(hex)) => Color::parse(&hex).or_else(|| {
+                eprintln!("Invalid flash_color {hex:?}, using the default");
+                Some(DEFAULT_FLASH_COLOR)
+            }),
+            _ => Some(DEFAULT_FLASH_COLOR),
+        };
     }
-}
 
-// VIEW FUNCTION
-fn view(state: &TimerApp) -> iced::Element<Message> {
-    let time_text = match &state.state {
-        TimerState::CountingDown(_) => format!(
-            "{:02}:{:02}",
-            state.current_display.as_secs() / 60,
-            state.current_display.as_secs() % 60
-        ),
-        _ => format!(
-            "{:02}:{:02}",
-            state.current_display.as_secs() / 60,
-            state.current_display.as_secs() % 60
-        ),
-    };
+    /// The time as shown on the main display.
+    fn clock_text(&self) -> String {
+        match &self.state {
+            // Mirror Dota's own clock: negative before the horn, no minute padding
+            TimerState::CountingDown(_) if self.game_clock => {
+                format!("-{}", format_game_clock(self.current_display))
+            }
+            // The countdown about to start, as it will first read
+            TimerState::Idle if self.game_clock && !self.current_display.is_zero() => {
+                format!("-{}", format_game_clock(self.current_display))
+            }
+            _ if self.game_clock => format_game_clock(self.current_display),
+            _ => format_clock(self.current_display),
+        }
+    }
 
-    // Start/Restart button logic
-    let (start_label, _is_restart) = match state.state {
-        TimerState::Idle => ("Start", false),
-        _ => ("Restart", true),
-    };
+    /// Keeps the ambience in step with the clock: playing while the game
+    /// clock runs, held while paused and stopped otherwise, at the master
+    /// volume scaled down, or silent while muted.
+    fn sync_ambience(&mut self) {
+        let running = matches!(self.state, TimerState::Running { .. });
+        let paused = matches!(self.state, TimerState::Paused(_));
+        let wanted = self.ambience.as_deref().filter(|_| running || paused);
+        let volume = if self.muted { 0.0 } else { self.volume * self.ambience_volume };
+        match wanted {
+            None => self.audio.stop_ambience(),
+            Some(path) if self.audio.ambience() != Some(path) => {
+                self.audio.stop_ambience();
+                if running {
+                    self.audio.play_ambience(path, volume);
+                }
+            }
+            Some(_) => self.audio.update_ambience(volume, paused),
+        }
+    }
 
-    let start_restart_button = widget::button(start_label)
-        .on_press(Message::StartRestart)
-        .padding(10);
+    /// The game time as `m:ss` for pasting into chat, negative during the
+    /// countdown. `None` while idle.
+    fn chat_time(&self) -> Option<String> {
+        let second = self.game_second()?;
+        let sign = if second < 0 { "-" } else { "" };
+        let time = format_game_clock(Duration::from_secs(second.unsigned_abs()));
+        Some(format!("{sign}{time}"))
+    }
 
-    // Pause/Resume button logic
-    let pause_resume_button = match state.state {
-        TimerState::Running{base_time:_, last_start:_} => Some(widget::button("Pause")
-            .on_press(Message::PauseResume)
-            .padding(10)),
-        TimerState::Paused(_) => Some(widget::button("Resume")
-            .on_press(Message::PauseResume)
-            .padding(10)),
-        _ => None,
-    };
+    /// The config files whose name contains the filter, ignoring case. The
+    /// selected one always stays listed.
+    fn filtered_files(&self) -> Vec<ConfigFile> {
+        let filter = self.file_filter.trim().to_lowercase();
+        self.yaml_files
+            .iter()
+            .filter(|file| {
+                self.selected_file.as_ref() == Some(&file.0)
+                    || file.to_string().to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect()
+    }
 
+    /// The time just copied, or why copying failed, while the notice still
+    /// shows.
+    fn copied_notice(&self) -> Option<Result<&str, &str>> {
+        let (result, at) = self.copied.as_ref()?;
+        let result = result.as_ref().map(String::as_str).map_err(String::as_str);
+        (at.elapsed() < COPIED_NOTICE).then_some(result)
+    }
 
-    let pick_list = widget::PickList::new(
-        state.yaml_files.as_slice(),
-        state.selected_file.clone(),
-        Message::LoadYaml,
-    )
-        .placeholder("Select Strategy File");
+    /// Whether it is day in the game and the seconds until it turns, or
+    /// `None` while idle or with `day_night` off. The countdown is daytime,
+    /// the same day that lasts until 5:00.
+    fn day_phase(&self) -> Option<(bool, i64)> {
+        if !self.day_night {
+            return None;
+        }
+        let second = self.game_second()?;
+        let length = i64::from(DAY_NIGHT_SECS);
+        let phase = second.max(0) / length;
+        Some((phase % 2 == 0, (phase + 1) * length - second))
+    }
 
-    let mut buttons = widget::row![start_restart_button];
-    if let Some(btn) = pause_resume_button {
-        buttons = buttons.push(btn);
+    /// The game time in words for text-to-speech, e.g. "twelve thirty-four",
+    /// or "game starts in forty-five" during the countdown. `None` while idle.
+    fn spoken_time(&self) -> Option<String> {
+        let secs = self.current_display.as_secs();
+        let clock = match (secs / 60, secs % 60) {
+            (0, secs) => format!("{} seconds", number_words(secs)),
+            (minutes, 0) => format!("{} minutes", number_words(minutes)),
+            (minutes, secs) if secs < 10 => {
+                format!("{} oh {}", number_words(minutes), number_words(secs))
+            }
+            (minutes, secs) => format!("{} {}", number_words(minutes), number_words(secs)),
+        };
+        match self.state {
+            TimerState::Idle => None,
+            TimerState::CountingDown(_) => Some(format!("game starts in {clock}")),
+            TimerState::Running { .. } | TimerState::Paused(_) => Some(clock),
+        }
     }
 
-    widget::column![
-        widget::text(time_text).size(25),
-        buttons,
-        pick_list
-    ]
-        .padding(12)
-        .into()
-}
\ No newline at end of file
+    /// What the overlay server hands out at `/state`.
+    fn overlay_json(&self) -> String {
+        let state = match self.state {
+            TimerState::Idle => "idle",
+            TimerState::CountingDown(_) => "countdown",
+            TimerState::Running { .. } => "running",
+            TimerState::Paused(_) => "paused",
+        };
+        let upcoming = self.upcoming_callouts();
+        let next = upcoming.iter().find(|(.., entry)| entry.enabled).map(|(_, left, entry)| {
+            serde_json::json!({ "name": entry.name(), "in": left.as_secs() })
+        });
+        serde_json::json!({
+            "state": state,
+            "game_second": self.game_second(),
+            "clock": self.clock_text(),
+            "next": next,
+        })
+        .to_string()
+    }
+
+    /// The time display's tint while a callout flash fades out, if any.
+    fn flash_tint(&self) -> Option<Color> {
+        let (color, elapsed) = (self.flash_color?, self.flashed_at?.elapsed());
+        if elapsed >= FLASH_DURATION {
+            return None;
+        }
+        // Fade from the flash color back to the theme's text color
+        let text = self.theme.theme().palette().text;
+        let t = elapsed.as_secs_f32() / FLASH_DURATION.as_secs_f32();
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+        Some(Color::from_rgb(mix(color.r, text.r), mix(color.g, text.g), mix(color.b, text.b)))
+    }
+
+    /// Removes every sound that is missing or can't be decoded so playback
+    /// never trips over it mid-game, and reports them in `config_error`.
+    fn drop_unplayable_sounds(&mut self) {
+        // With a fallback configured, broken sounds stay so the fallback plays in their place
+        let keep_broken = self.audio.has_fallback();
+        let mut broken = Vec::new();
+        let mut playable = |path: &str| match audio::check_playable(path) {
+            Ok(()) => true,
+            Err(err) => {
+                if keep_broken {
+                    eprintln!("Cannot play {path}, the fallback sound will play instead: {err}");
+                } else {
+                    eprintln!("Skipping {path}: {err}");
+                }
+                broken.push(path.to_string());
+                keep_broken
+            }
+        };
+
+        // Only the broken parts of a sequence go; spoken entries have no file to check
+        self.audio_map.retain(|_, entry| {
+            entry.file = entry.file.take().filter(|path| playable(path));
+            entry.files.retain(|path| playable(path));
+            entry.pre_sound = entry.pre_sound.take().filter(|path| playable(path));
+            entry.has_sound()
+        });
+        self.repeat_rules.retain(|rule| playable(&rule.sound));
+        self.rosh_sound = self.rosh_sound.take().filter(|path| playable(path));
+        self.cooldown_sound = self.cooldown_sound.take().filter(|path| playable(path));
+        for preset in &mut self.cooldown_presets {
+            preset.sound = preset.sound.take().filter(|path| playable(path));
+        }
+        self.horn_sound = self.horn_sound.take().filter(|path| playable(path));
+        self.beep_sound = self.beep_sound.take().filter(|path| playable(path));
+        self.pre_announce_sound = self.pre_announce_sound.take().filter(|path| playable(path));
+        self.ambience = self.ambience.take().filter(|path| playable(path));
+
+        broken.sort();
+        broken.dedup();
+        self.config_error = if broken.is_empty() {
+            None
+        } else {
+            Some(format!("Cannot play: {}", broken.join(", ")))
+        };
+    }
+
+    /// The next few callouts from `audio_map` that haven't fired yet, with
+    /// their game millisecond and the time left until each, rounded up to
+    /// whole seconds. Empty while idle.
+    fn upcoming_callouts(&self) -> Vec<(i64, Duration, &AudioEntry)> {
+        let (Some(second), Some(now)) = (self.game_second(), self.game_millis()) else {
+            return Vec::new();
+        };
+
+        self.audio_map
+            .range(second * 1000..)
+            .filter(|(millis, _)| !self.fired.contains(&Trigger::Callout(**millis)))
+            .take(UPCOMING_LIMIT)
+            .map(|(millis, entry)| {
+                let left = Duration::from_millis((millis - now).max(0) as u64);
+                (*millis, round_up_secs(left), entry)
+            })
+            .collect()
+    }
+
+    /// The soonest callout, repeat, ticked warning or horn still to play,
+    /// with the time left until it rounded up to whole seconds and its name.
+    /// `None` while idle or once nothing is left.
+    fn next_event(&self) -> Option<(Duration, String)> {
+        let (Some(second), Some(now)) = (self.game_second(), self.game_millis()) else {
+            return None;
+        };
+
+        let callout = self
+            .audio_map
+            .range(second * 1000..)
+            .filter(|(_, entry)| entry.enabled)
+            .find(|(millis, _)| !self.fired.contains(&Trigger::Callout(**millis)))
+            .map(|(millis, entry)| (*millis, entry.name()));
+        // Repeats and warnings only run after the horn
+        let from = u64::try_from(second).unwrap_or(0);
+        let next_of = |rule: &RepeatRule, trigger: &dyn Fn(u64) -> Trigger| {
+            let next = rule.next_from(from)?;
+            if self.fired.contains(&trigger(next)) {
+                rule.next_from(next + 1)
+            } else {
+                Some(next)
+            }
+        };
+        let repeats = self.repeat_rules.iter().enumerate().filter_map(|(index, rule)| {
+            let next = next_of(rule, &|second| Trigger::Repeat { rule: index, second })?;
+            Some((next as i64 * 1000, rule.name()))
+        });
+        let warnings = self.warning_rules.iter().enumerate().filter_map(|(index, rule)| {
+            if !self.warnings[index] {
+                return None;
+            }
+            let next = next_of(rule, &|second| Trigger::Warning { warning: index, second })?;
+            Some((next as i64 * 1000, warning_name(index)))
+        });
+        let horn = (self.horn_sound.is_some() && now <= 0 && !self.fired.contains(&Trigger::Horn))
+            .then(|| (0, "Horn".to_string()));
+        let (millis, name) = callout
+            .into_iter()
+            .chain(repeats)
+            .chain(warnings)
+            .chain(horn)
+            .min_by_key(|(millis, _)| *millis)?;
+        let left = Duration::from_millis((millis - now).max(0) as u64);
+        Some((round_up_secs(left), name))
+    }
+
+    /// A one-line check that the loaded file parsed as expected, such as
+    /// "12 triggers, last at 40:00".
+    fn config_summary(&self) -> String {
+        match self.audio_map.last_key_value() {
+            Some((last, _)) => {
+                let count = self.audio_map.len();
+                let plural = if count == 1 { "" } else { "s" };
+                let sign = if *last < 0 { "-" } else { "" };
+                let last = format_game_clock(Duration::from_millis(last.unsigned_abs()));
+                format!("{count} trigger{plural}, last at {sign}{last}")
+            }
+            None if self.repeat_rules.is_empty() => "No callouts in this file".to_string(),
+            None => {
+                let count = self.repeat_rules.len();
+                let plural = if count == 1 { "" } else { "s" };
+                format!("{count} repeating callout{plural}")
+            }
+        }
+    }
+
+    /// The next callout that hasn't fired yet while paused, for stepping
+    /// through them one at a time. `None` unless paused.
+    fn next_callout_millis(&self) -> Option<i64> {
+        if !matches!(self.state, TimerState::Paused(_)) {
+            return None;
+        }
+        let now = self.game_second()? * 1000;
+        self.audio_map
+            .range(now..)
+            .map(|(millis, _)| *millis)
+            .find(|millis| !self.fired.contains(&Trigger::Callout(*millis)))
+    }
+
+    /// How far the clock is from the previous callout to the next one, as a
+    /// 0.0–1.0 fraction. `None` while idle or when nothing is left to play.
+    fn callout_progress(&self) -> Option<f32> {
+        // Seconds on the game clock, negative during the countdown
+        let (now, start) = match self.state {
+            TimerState::Idle => return None,
+            TimerState::CountingDown(_) => (
+                -self.current_display.as_secs_f32(),
+                -self.countdown.as_secs_f32(),
+            ),
+            _ => (self.current_display.as_secs_f32(), 0.0),
+        };
+
+        let times = self.audio_map.keys().map(|millis| *millis as f32 / 1000.0);
+        let next = times.clone().find(|time| *time > now)?;
+        let previous = times.take_while(|time| *time <= now).last().unwrap_or(start);
+        Some(((now - previous) / (next - previous)).clamp(0.0, 1.0))
+    }
+
+    /// Starts Roshan and the cooldowns counting again after a pause froze them.
+    fn resume_side_timers(&mut self, now: Instant) {
+        if let Some(rosh) = &mut self.rosh_state {
+            rosh.resume(now);
+        }
+        for cooldown in self.cooldowns.iter_mut().filter(|c| !c.remaining.is_zero()) {
+            cooldown.last_tick.get_or_insert(now);
+        }
+    }
+
+    fn tick_rosh(&mut self, now: Instant) {
+        let Some(rosh) = &mut self.rosh_state else {
+            return;
+        };
+        let Some(resumed_at) = rosh.resumed_at else {
+            return;
+        };
+        rosh.elapsed = rosh.base + now.duration_since(resumed_at);
+
+        if rosh.elapsed >= ROSH_MIN_RESPAWN && !rosh.min_alert_played {
+            rosh.min_alert_played = true;
+            if let Some(path) = &self.rosh_sound {
+                if !self.muted {
+                    self.audio.play(path, self.volume);
+                }
+                let second = self.game_second().unwrap_or(0);
+                self.trigger_log.record(second, "Roshan respawn window", self.muted);
+            }
+        }
+    }
+
+    fn tick_cooldowns(&mut self, now: Instant) {
+        let second = self.game_second().unwrap_or(0);
+        for cooldown in &mut self.cooldowns {
+            let Some(last_tick) = cooldown.last_tick else {
+                continue;
+            };
+            cooldown.remaining = cooldown.remaining.saturating_sub(now.duration_since(last_tick));
+            cooldown.last_tick = Some(now);
+
+            if cooldown.remaining.is_zero() {
+                cooldown.last_tick = None;
+                if let Some(path) = &cooldown.sound {
+                    if !self.muted {
+                        self.audio.play(path, self.volume);
+                    }
+                }
+                self.trigger_log.record(second, &format!("{} ready", cooldown.label), self.muted);
+            }
+        }
+    }
+
+    /// Plays `horn_sound` as the game clock starts, once per game.
+    fn sound_horn(&mut self) {
+        if let Some(path) = &self.horn_sound {
+            if self.fired.insert(Trigger::Horn) {
+                if !self.muted {
+                    self.audio.play(path, self.volume);
+                }
+                self.trigger_log.record(0, "Horn", self.muted);
+            }
+        }
+    }
+
+    /// Fires every trigger from the last checked game second up to the
+    /// current one, so none is missed when a tick straddles its second.
+    fn check_triggers(&mut self) {
+        self.check_triggers_at(Instant::now());
+    }
+
+    /// Plays whatever fell due between the last check and the game time at
+    /// `now`.
+    fn check_triggers_at(&mut self, now: Instant) {
+        let Some(now) = self.game_millis_at(now) else {
+            return;
+        };
+        // Right after a start, everything in the current second is due
+        let from = self.checked_through.map_or(now.div_euclid(1000) * 1000, |last| last + 1);
+        self.checked_through = Some(now);
+        if from > now {
+            return;
+        }
+        // After a long stall, e.g. the laptop sleeping, whatever is further
+        // behind than the grace window counts as played instead of all
+        // playing at once. The window is wall-clock time, so fast-forwarding
+        // doesn't make everything late
+        let grace = self.late_grace.mul_f32(self.speed).as_millis() as i64;
+        let stale_before = now - grace;
+        if from < stale_before {
+            let stale: Vec<Trigger> = self
+                .triggers_before(stale_before)
+                .into_iter()
+                .filter(|trigger| trigger.game_millis() >= from && !self.fired.contains(trigger))
+                .collect();
+            if !stale.is_empty() {
+                eprintln!(
+                    "Skipping {} triggers more than {}s late",
+                    stale.len(),
+                    self.late_grace.as_secs()
+                );
+            }
+            self.fired.extend(stale);
+        }
+        self.check_pre_announces(from, now);
+        self.check_audio_triggers(from, now);
+
+        // Beeps and repeats fall on whole seconds
+        let (from, to) = (ceil_secs(from), now.div_euclid(1000));
+        if from <= to {
+            self.check_beeps(from, to);
+            self.check_repeat_triggers(from, to);
+            self.check_warnings(from, to);
+        }
+    }
+
+    fn check_beeps(&mut self, from: i64, to: i64) {
+        let Some(path) = &self.beep_sound else {
+            return;
+        };
+        for second in from.max(BEEP_SECONDS.start)..=to.min(BEEP_SECONDS.end - 1) {
+            if self.fired.insert(Trigger::Beep(second)) && !self.muted {
+                self.audio.play(path, self.volume);
+            }
+        }
+    }
+
+    fn check_repeat_triggers(&mut self, from: i64, to: i64) {
+        // Repeats only run after the horn
+        let Ok(to) = u64::try_from(to) else {
+            return;
+        };
+        let from = u64::try_from(from).unwrap_or(0);
+
+        for (index, rule) in self.repeat_rules.iter().enumerate() {
+            for second in (from..=to).filter(|second| rule.fires_at(*second)) {
+                if self.fired.insert(Trigger::Repeat { rule: index, second }) {
+                    if !self.muted {
+                        self.audio.play(&rule.sound, self.volume);
+                    }
+                    self.trigger_log.record(second as i64, &rule.name(), self.muted);
+                    self.flashed_at = Some(Instant::now());
+                }
+            }
+        }
+    }
+
+    /// The ticked built-in warnings due in `from..=to`. Unticked ones still
+    /// count as fired, so ticking one mid-game doesn't play a stale warning.
+    fn check_warnings(&mut self, from: i64, to: i64) {
+        let Ok(to) = u64::try_from(to) else {
+            return;
+        };
+        let from = u64::try_from(from).unwrap_or(0);
+
+        for (index, rule) in self.warning_rules.iter().enumerate() {
+            for second in (from..=to).filter(|second| rule.fires_at(*second)) {
+                let new = self.fired.insert(Trigger::Warning { warning: index, second });
+                if new && self.warnings[index] {
+                    if !self.muted {
+                        self.audio.play(&rule.sound, self.volume);
+                    }
+                    self.trigger_log.record(second as i64, &warning_name(index), self.muted);
+                    self.flashed_at = Some(Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Heads-ups for the callouts in `range` of game milliseconds.
+    fn pre_announces(&self, range: impl RangeBounds<i64>) -> impl Iterator<Item = Trigger> + '_ {
+        self.audio_map.range(range).filter_map(|(millis, entry)| {
+            let secs = entry.pre_announce.unwrap_or(self.pre_announce_secs);
+            let secs = secs.min(MAX_PRE_ANNOUNCE_SECS);
+            let at = millis - i64::from(secs) * 1000;
+            let in_countdown = self.running_only && at < 0 && *millis >= 0;
+            let due = entry.enabled && secs > 0 && !in_countdown;
+            due.then_some(Trigger::PreAnnounce { at, callout: *millis })
+        })
+    }
+
+    /// Gives the heads-ups due in `from..=to`, separately from the callouts
+    /// themselves. One whose time had already passed when the config loaded
+    /// is skipped, since only new ticks are checked.
+    fn check_pre_announces(&mut self, from: i64, to: i64) {
+        let ahead = to.saturating_add(i64::from(MAX_PRE_ANNOUNCE_SECS) * 1000);
+        let due: Vec<Trigger> = self
+            .pre_announces(from..=ahead)
+            .filter(|trigger| (from..=to).contains(&trigger.game_millis()))
+            .collect();
+        for trigger in due {
+            let Trigger::PreAnnounce { at, callout } = trigger else {
+                continue;
+            };
+            if !self.fired.insert(trigger) {
+                continue;
+            }
+            let entry = &self.audio_map[&callout];
+            let text = format!("{} in {}", entry.name(), (callout - at) / 1000);
+            if !self.muted {
+                match entry.pre_sound.as_deref().or(self.pre_announce_sound.as_deref()) {
+                    Some(path) => self.audio.play(path, self.volume),
+                    None => self.audio.say(&text, self.volume),
+                }
+            }
+            self.trigger_log.record(at.div_euclid(1000), &text, self.muted);
+        }
+    }
+
+    fn check_audio_triggers(&mut self, from: i64, to: i64) {
+        for (millis, entry) in self.audio_map.range(from..=to) {
+            // Muted triggers still count as fired so they don't play on unmute, and
+            // disabled ones so they stay inert if switched back on behind the clock
+            if self.fired.insert(Trigger::Callout(*millis)) && entry.enabled {
+                if !self.muted {
+                    self.audio.play_entry(entry, self.volume);
+                }
+                self.trigger_log.record(millis.div_euclid(1000), &entry.name(), self.muted);
+                self.last_callout = Some(entry.name());
+                self.flashed_at = Some(Instant::now());
+            }
+        }
+    }
+}
+
+fn main() -> iced::Result {
+    let args = match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(err) = args.config.as_deref().map_or(Ok(()), cli::check_config) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+
+    // Held until `main` returns, so a second launch can't double the callouts
+    let _lock = match InstanceLock::acquire(args.title.as_deref()) {
+        Ok(lock) => lock,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    // Built up front so the window can open at the restored size and level
+    let mut app = TimerApp {
+        title: args.title,
+        ..TimerApp::default()
+    };
+    if let Some(file) = args.config {
+        app.load_file(file);
+    }
+    if let Some(port) = args.overlay_port {
+        app.overlay = Overlay::start(port);
+    }
+    if let Some(speed) = args.speed {
+        app.set_speed(speed);
+    }
+    let startup = if args.autostart {
+        Task::done(Message::StartRestart)
+    } else {
+        Task::none()
+    };
+    let size = if app.compact {
+        compact_window_size(app.font_size, app.ui_scale)
+    } else {
+        app.window_size.unwrap_or_else(|| window_size(app.font_size, app.ui_scale))
+    };
+    let position = match app.window_position {
+        Some(position) if is_on_screen(position, size) => window::Position::Specific(position),
+        _ => window::Position::Centered,
+    };
+
+    iced::application(window_title, update, view)
+        .subscription(subscription)
+        .theme(|state| state.theme.theme())
+        .scale_factor(|state| state.ui_scale.into())
+        .style(style)
+        .window(window::Settings {
+            level: window_level(app.always_on_top),
+            size,
+            position,
+            transparent: true,
+            // Closing is handled in `update` so the window geometry gets saved first
+            exit_on_close_request: false,
+            ..window::Settings::default()
+        })
+        .run_with(move || (app, startup))
+}
+
+// Only the background fades, so the text stays fully legible
+fn style(state: &TimerApp, theme: &Theme) -> Appearance {
+    let mut appearance = theme.default_style();
+    appearance.background_color.a = state.theme.background_alpha() * state.opacity;
+    appearance
+}
+
+fn subscription(state: &TimerApp) -> Subscription<Message> {
+    let tick = match &state.state {
+        TimerState::CountingDown(_) | TimerState::Running{ .. } => {
+            time::every(state.tick).map(Message::Tick)
+        }
+        // Keep animating a callout flash even while paused or idle
+        _ if state.flash_tint().is_some() => {
+            time::every(state.tick).map(Message::Tick)
+        }
+        // The Roshan timer can outlive a restart, so it may still be running while idle
+        _ if state.rosh_state.as_ref().is_some_and(|rosh| rosh.resumed_at.is_some()) => {
+            time::every(state.tick).map(Message::Tick)
+        }
+        _ if state.cooldowns.iter().any(|cooldown| cooldown.last_tick.is_some()) => {
+            time::every(state.tick).map(Message::Tick)
+        }
+        // Until the "Copied" confirmation goes away
+        _ if state.copied_notice().is_some() => time::every(state.tick).map(Message::Tick),
+        // The wall clock only shows whole seconds, so a slow tick is plenty
+        TimerState::Idle | TimerState::Paused(_) if state.wall_clock => {
+            time::every(Duration::from_secs(1)).map(Message::Tick)
+        }
+        _ => Subscription::none(),
+    };
+
+    let checkpoint = if matches!(state.state, TimerState::Idle) {
+        Subscription::none()
+    } else {
+        time::every(RECOVERY_INTERVAL).map(|_| Message::SaveRecovery)
+    };
+
+    // Poll the selected file's modification time to pick up edits
+    let watch = if state.selected_file.is_some() {
+        time::every(Duration::from_secs(1)).map(|_| Message::CheckConfig)
+    } else {
+        Subscription::none()
+    };
+
+    // Only presses no widget captured reach us, so a focused pick list keeps its keys
+    Subscription::batch([
+        tick,
+        checkpoint,
+        watch,
+        keyboard::on_key_press(|key, _| Some(Message::KeyPressed(key))),
+        keyboard::on_key_release(|key, _| Some(Message::KeyReleased(key))),
+        Subscription::run(hotkeys::events).map(Message::GlobalHotkey),
+        Subscription::run(tray::events).map(Message::Tray),
+        event::listen_with(|event, _, _| match event {
+            Event::Window(window::Event::Moved(position)) => Some(Message::WindowMoved(position)),
+            Event::Window(window::Event::Focused) => Some(Message::WindowFocused(true)),
+            Event::Window(window::Event::Unfocused) => Some(Message::WindowFocused(false)),
+            Event::Window(window::Event::Resized(size)) => Some(Message::WindowResized(size)),
+            _ => None,
+        }),
+        window::close_requests().map(Message::CloseRequested),
+    ])
+}
+
+/// Maps a keyboard shortcut to the message it stands for.
+fn shortcut(key: &Key) -> Option<Message> {
+    match key.as_ref() {
+        Key::Named(key::Named::Space) => Some(Message::PauseResume),
+        Key::Named(key::Named::Enter) => Some(Message::RestartPressed),
+        Key::Character(c) if c.eq_ignore_ascii_case("r") => Some(Message::RestartPressed),
+        Key::Character(c) if c.eq_ignore_ascii_case("c") => Some(Message::ToggleCompact),
+        Key::Character(c) if c.eq_ignore_ascii_case("u") => Some(Message::UndoAdjust),
+        Key::Character(c) if c.eq_ignore_ascii_case("t") => Some(Message::CopyTime),
+        Key::Named(key::Named::PageUp) => Some(Message::CycleConfig(-1)),
+        Key::Named(key::Named::PageDown) => Some(Message::CycleConfig(1)),
+        _ => None,
+    }
+}
+
+// UPDATE FUNCTION
+fn update(state: &mut TimerApp, message: Message) -> Task<Message> {
+    let task = handle_message(state, message);
+    state.sync_ambience();
+    state.overlay.publish(|| state.overlay_json());
+    task
+}
+
+fn handle_message(state: &mut TimerApp, message: Message) -> Task<Message> {
+    match message {
+        Message::RestartPressed => {
+            if let Some(file) = state.highlighted_file.take() {
+                return handle_message(state, Message::LoadYaml(file));
+            }
+            let in_game =
+                matches!(state.state, TimerState::Running { .. } | TimerState::CountingDown(_));
+            if in_game && !state.restart_confirming() {
+                state.restart_armed = Some(Instant::now());
+                return Task::none();
+            }
+            return handle_message(state, Message::StartRestart);
+        },
+        Message::StartRestart => {
+            state.restart_armed = None;
+            state.auto_paused = false;
+            // Reload the selected file if present, keeping the last good config if it broke
+            if state.selected_file.is_some() {
+                state.config_modified = state.sources_modified();
+                if let Err(err) = state.reload_config() {
+                    state.config_error = Some(err);
+                }
+            }
+
+            // Always reset to initial state when clicking Start/Restart
+            state.recovery = None;
+            state.current_display = state.countdown;
+            state.fired.clear();
+            state.adjustments.clear();
+            state.audio.stop_ambience();
+            state.checked_through = None;
+            state.last_callout = None;
+            if state.countdown.is_zero() {
+                state.state = TimerState::Running {
+                    base_time: Duration::ZERO,
+                    last_start: Instant::now(),
+                };
+                // Second-0 callouts play now rather than waiting for the first tick
+                state.sound_horn();
+                state.check_triggers();
+            } else {
+                state.state = TimerState::CountingDown(Instant::now());
+            }
+        },
+        // Picks up an edited config mid-game: only triggers still ahead will play
+        Message::ApplyConfigNow => {
+            state.config_modified = state.sources_modified();
+            match state.reload_config() {
+                Ok(()) => {
+                    if let Some(now) = state.game_millis() {
+                        state.rebuild_triggered(now + 1);
+                    }
+                }
+                Err(err) => state.config_error = Some(err),
+            }
+        },
+        // Every timer freezes and resumes from the same instant, so they stay in step
+        Message::PauseResume => {
+            // A pause or resume by hand takes over from the focus-driven one
+            state.auto_paused = false;
+            let now = Instant::now();
+            match &state.state {
+                TimerState::Running { base_time, last_start } => {
+                    let elapsed = *base_time + now.duration_since(*last_start).mul_f32(state.speed);
+                    state.state = TimerState::Paused(elapsed);
+                    state.current_display = elapsed;
+                    if let Some(rosh) = &mut state.rosh_state {
+                        rosh.pause(now);
+                    }
+                    for cooldown in state.cooldowns.iter_mut().filter(|c| !c.remaining.is_zero()) {
+                        if let Some(last_tick) = cooldown.last_tick.take() {
+                            cooldown.remaining =
+                                cooldown.remaining.saturating_sub(now.duration_since(last_tick));
+                        }
+                    }
+                },
+                TimerState::Paused(elapsed) => {
+                    state.state = TimerState::Running {
+                        base_time: *elapsed,
+                        last_start: now,
+                    };
+                    state.resume_side_timers(now);
+                },
+                _ => {}
+            }
+        },
+        Message::AdjustTime(secs) => {
+            state.adjust_time(secs);
+        },
+        Message::UndoAdjust => {
+            state.undo_adjust();
+        },
+        Message::CopyTime => {
+            if let Some(time) = state.chat_time() {
+                let result = match state.clipboard.write(&time) {
+                    Ok(()) => Ok(time),
+                    Err(err) => {
+                        eprintln!("Failed to copy {time}: {err}");
+                        Err(err.to_string())
+                    }
+                };
+                state.copied = Some((result, Instant::now()));
+            }
+        },
+        // Jumps the paused clock to the next callout and plays it, staying paused
+        Message::StepToNext => {
+            if let Some(millis) = state.next_callout_millis() {
+                let time = Duration::from_millis(millis as u64);
+                state.state = TimerState::Paused(time);
+                state.current_display = time;
+                state.rebuild_triggered(millis);
+                state.check_triggers();
+            }
+        },
+        Message::SetSpeed(speed) => state.set_speed(speed),
+        Message::SetTheme(theme) => {
+            state.theme = theme;
+        },
+        Message::CycleConfig(step) => {
+            let count = state.yaml_files.len() as isize;
+            if count == 0 {
+                return Task::none();
+            }
+            let from = state.highlighted_file.as_ref().or(state.selected_file.as_ref());
+            let current = state.yaml_files.iter().position(|file| from == Some(&file.0));
+            // With nothing selected yet, stepping down starts at the top
+            let index = match current {
+                Some(index) => (index as isize + step).rem_euclid(count),
+                None if step > 0 => 0,
+                None => count - 1,
+            };
+            let file = &state.yaml_files[index as usize].0;
+            state.highlighted_file =
+                Some(file.clone()).filter(|file| state.selected_file.as_ref() != Some(file));
+        },
+        Message::LoadYaml(file) => {
+            let (font_size, ui_scale) = (state.font_size, state.ui_scale);
+            state.selected_profile = None;
+            state.merged_files.clear();
+            state.load_file(file);
+
+            let mut saved = SavedState::load();
+            saved.selected_file = state.selected_file.clone();
+            saved.save();
+
+            if state.font_size != font_size || state.ui_scale != ui_scale {
+                return resize_window(state);
+            }
+        },
+        Message::MergeYaml(file) => {
+            state.merged_files.push(file);
+            if let Err(err) = state.reload_config() {
+                state.merged_files.pop();
+                state.config_error = Some(err);
+            }
+            state.config_modified = state.sources_modified();
+        },
+        Message::ClearMerged => {
+            state.merged_files.clear();
+            if let Err(err) = state.reload_config() {
+                state.config_error = Some(err);
+            }
+            state.config_modified = state.sources_modified();
+        },
+        Message::SelectProfile(name) => {
+            state.selected_profile = Some(name);
+            if let Err(err) = state.reload_config() {
+                state.config_error = Some(err);
+            }
+        },
+        // Only a pause focus caused is undone by it, never one made by hand
+        Message::WindowFocused(focused) => {
+            let running = matches!(state.state, TimerState::Running { .. });
+            if !focused && running && state.pause_on_unfocus {
+                let task = handle_message(state, Message::PauseResume);
+                state.auto_paused = true;
+                return task;
+            }
+            if focused && state.auto_paused && matches!(state.state, TimerState::Paused(_)) {
+                return handle_message(state, Message::PauseResume);
+            }
+        },
+        Message::WindowMoved(position) => {
+            state.window_position = Some(position);
+        },
+        Message::WindowResized(size) => {
+            state.window_size = Some(size);
+        },
+        Message::CloseRequested(id) => {
+            Recovery::clear();
+            let mut saved = SavedState::load();
+            // The compact size is derived from the font, so keep the full one
+            let size = if state.compact { state.expanded_size } else { state.window_size };
+            saved.window_size = size.map(|size| (size.width, size.height));
+            saved.window_position = state.window_position.map(|point| (point.x, point.y));
+            saved.save();
+            return window::close(id);
+        },
+        Message::SaveRecovery => {
+            let phase = match state.state {
+                TimerState::Idle => return Task::none(),
+                TimerState::CountingDown(_) => Phase::Countdown,
+                TimerState::Running { .. } => Phase::Running,
+                TimerState::Paused(_) => Phase::Paused,
+            };
+            Recovery {
+                phase,
+                game_millis: state.game_millis().unwrap_or(0),
+                selected_file: state.selected_file.clone(),
+                merged_files: state.merged_files.clone(),
+                selected_profile: state.selected_profile.clone(),
+                saved_at: 0,
+            }
+            .save();
+        },
+        Message::ResumeRecovery => {
+            if let Some(recovery) = state.recovery.take() {
+                let file = recovery.selected_file.clone().filter(|file| Path::new(file).is_file());
+                if let Some(file) = file {
+                    state.selected_profile = recovery.selected_profile.clone();
+                    state.merged_files = recovery.merged_files.clone();
+                    state.load_file(file);
+                }
+                state.jump_to(recovery.game_millis_now(), recovery.phase == Phase::Paused);
+            }
+        },
+        Message::DismissRecovery => {
+            state.recovery = None;
+            Recovery::clear();
+        },
+        Message::SyncInputChanged(input) => {
+            state.sync_input = input;
+        },
+        Message::FilterChanged(filter) => {
+            state.file_filter = filter;
+        },
+        Message::SyncTo(time) => {
+            // Syncing out of a pause resumes everything, as Resume would
+            let now = Instant::now();
+            if matches!(state.state, TimerState::Paused(_)) {
+                state.resume_side_timers(now);
+            }
+            state.auto_paused = false;
+            state.state = TimerState::Running {
+                base_time: time,
+                last_start: now,
+            };
+            // Undo steps were taken against the clock being replaced
+            state.adjustments.clear();
+            state.current_display = time;
+            state.last_callout = None;
+            // The entered second counts as passed too, so nothing plays the moment it syncs
+            state.rebuild_triggered((time.as_secs() as i64 + 1) * 1000);
+            state.sync_input.clear();
+        },
+        Message::Editor(action) => match action {
+            EditorAction::Toggle => {
+                state.editor.open = !state.editor.open;
+                // Rescanned on every open, to pick up sounds added in the meantime
+                if state.editor.open {
+                    state.editor.scan_library(state.sound_library.as_deref());
+                }
+            }
+            EditorAction::TimeChanged(time) => state.editor.time = time,
+            EditorAction::FileChanged(file) => state.editor.file = file,
+            EditorAction::LabelChanged(label) => state.editor.label = label,
+            EditorAction::QueryChanged(query) => state.editor.query = query,
+            // Plays even while muted, like the Test button
+            EditorAction::Preview(path) => {
+                let path = state.editor.resolve_path(&path);
+                state.audio.play(&path, state.volume);
+            }
+            EditorAction::Pick(path) => state.editor.file = path,
+            // Edits are put through a reload so they get their lead time like the
+            // config's own callouts
+            EditorAction::Add => {
+                if let Some(second) = state.editor.second() {
+                    match state.editor.entry() {
+                        Ok(entry) => {
+                            state.editor.set(second, Some(entry));
+                            if let Err(err) = state.reload_config() {
+                                state.config_error = Some(err);
+                            }
+                        }
+                        Err(err) => state.config_error = Some(err),
+                    }
+                }
+            }
+            EditorAction::Remove => {
+                if let Some(second) = state.editor.second() {
+                    if state.is_written_at(i64::from(second) * 1000) {
+                        state.editor.set(second, None);
+                        if let Err(err) = state.reload_config() {
+                            state.config_error = Some(err);
+                        }
+                    }
+                }
+            }
+            // Rewriting the file mid-game would trigger a reload under the running timer
+            EditorAction::Save => {
+                if !matches!(state.state, TimerState::Idle) {
+                    state.config_error = Some("Stop the timer before saving".to_string());
+                } else if let Some(file) = state.selected_file.clone() {
+                    let result = state.editor.save(&file).and_then(|()| state.reload_config());
+                    state.config_modified = state.sources_modified();
+                    if let Err(err) = result {
+                        state.config_error = Some(err);
+                    }
+                }
+            }
+            EditorAction::SaveAsChanged(name) => state.editor.save_as = name,
+            // A new file, so unlike Save it can't disturb the running timer
+            EditorAction::SaveAs => {
+                let fade = state.audio.fade();
+                let settings = ExportSettings {
+                    pre_announce_secs: state.pre_announce_secs,
+                    pre_announce_sound: state.pre_announce_sound.clone(),
+                    fade_in_ms: fade.fade_in.as_millis() as u32,
+                    fade_out_ms: fade.fade_out.as_millis() as u32,
+                };
+                // Callouts whose sounds won't play right now are still part of the config
+                let (audio, repeat) = (&state.loaded_audio, &state.loaded_repeat);
+                match editor::export(&state.editor.save_as, audio, repeat, &settings) {
+                    Ok(_) => {
+                        let files = get_yaml_files(&config_dirs());
+                        state.yaml_files = files.into_iter().map(ConfigFile).collect();
+                        state.editor.save_as.clear();
+                        state.config_error = None;
+                    }
+                    Err(err) => state.config_error = Some(err),
+                }
+            }
+        },
+        Message::SelectAudioDevice(name) => {
+            let device = (name != DEFAULT_AUDIO_DEVICE).then_some(name);
+            let mut saved = SavedState::load();
+            saved.audio_device = device.clone();
+            saved.save();
+            state.audio.set_device(device);
+        },
+        Message::ToggleCallout(millis) => {
+            let entries = [state.audio_map.get_mut(&millis), state.loaded_audio.get_mut(&millis)];
+            for entry in entries.into_iter().flatten() {
+                entry.enabled = !entry.enabled;
+            }
+        },
+        // Plays even while muted, since it was asked for explicitly
+        Message::TestSound(second) => {
+            if let Some(entry) = state.audio_map.get(&second) {
+                state.audio.play_entry(entry, state.volume);
+            }
+        },
+        // Loads the callout into the editor, ready to change or remove
+        Message::SelectCallout(millis) => {
+            if let Some(entry) = state.audio_map.get(&millis) {
+                state.selected_callout = Some(millis);
+                let written = state.written_at.get(&millis).copied().unwrap_or(millis);
+                state.editor.time = format_editor_time(written);
+                state.editor.file = entry.sounds().next().unwrap_or_default().to_string();
+                state.editor.label = entry.label.clone().unwrap_or_default();
+            }
+        },
+        Message::SetFontSize(size) => {
+            state.font_size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+            return resize_window(state);
+        },
+        Message::VolumeChanged(volume) => {
+            state.volume = volume.clamp(0.0, 1.0);
+        },
+        Message::ToggleMute => {
+            state.muted = !state.muted;
+        },
+        Message::DismissAlert => {
+            state.audio.dismiss_alert();
+        },
+        Message::RoshKilled => {
+            // Starts frozen if the game clock is paused, and runs on resume
+            let paused = matches!(state.state, TimerState::Paused(_));
+            state.rosh_state = Some(RoshTimer {
+                base: Duration::ZERO,
+                resumed_at: (!paused).then(Instant::now),
+                elapsed: Duration::ZERO,
+                min_alert_played: false,
+            });
+        },
+        Message::RoshClear => {
+            state.rosh_state = None;
+        },
+        Message::StartCooldown(preset) => {
+            let Some(preset) = state.cooldown_presets.get(preset) else {
+                return Task::none();
+            };
+            let paused = matches!(state.state, TimerState::Paused(_));
+            state.cooldowns.push(Cooldown {
+                label: preset.label.clone(),
+                remaining: preset.length,
+                sound: preset.sound.clone().or_else(|| state.cooldown_sound.clone()),
+                last_tick: (!paused).then(Instant::now),
+            });
+        },
+        Message::RemoveCooldown(index) => {
+            if index < state.cooldowns.len() {
+                state.cooldowns.remove(index);
+            }
+        },
+        Message::KeyPressed(key) => {
+            // A key that is already held down is auto-repeat, not a new press
+            if state.held_keys.insert(key.clone()) {
+                if let Some(message) = shortcut(&key) {
+                    return handle_message(state, message);
+                }
+            }
+        },
+        Message::KeyReleased(key) => {
+            state.held_keys.remove(&key);
+        },
+        Message::GlobalHotkey(id) => match state.hotkeys.action(id) {
+            Some(HotkeyAction::StartRestart) => {
+                return handle_message(state, Message::RestartPressed)
+            }
+            Some(HotkeyAction::PauseResume) => return handle_message(state, Message::PauseResume),
+            // Spoken even while muted, since it was asked for explicitly
+            Some(HotkeyAction::AnnounceTime) => {
+                if let Some(text) = state.spoken_time() {
+                    state.audio.say(&text, state.volume);
+                }
+            }
+            None => {}
+        },
+        Message::Tray(action) => match action {
+            TrayAction::StartRestart => return handle_message(state, Message::RestartPressed),
+            TrayAction::PauseResume => return handle_message(state, Message::PauseResume),
+            // Goes through the close request so the window geometry is still saved
+            TrayAction::Quit => {
+                return window::get_latest()
+                    .and_then(|id| Task::done(Message::CloseRequested(id)));
+            }
+        },
+        Message::SetOpacity(opacity) => {
+            state.opacity = opacity.clamp(MIN_OPACITY, 1.0);
+        },
+        Message::ToggleAlwaysOnTop => {
+            state.always_on_top = !state.always_on_top;
+
+            let mut saved = SavedState::load();
+            saved.always_on_top = state.always_on_top;
+            saved.save();
+
+            let level = window_level(state.always_on_top);
+            return window::get_latest().and_then(move |id| window::change_level(id, level));
+        },
+        Message::ToggleWarning(index) => {
+            state.warnings[index] = !state.warnings[index];
+            let sound = &state.warning_rules[index].sound;
+            if state.warnings[index] {
+                if let Err(err) = audio::check_playable(sound) {
+                    eprintln!("Cannot play the warning sound {sound}: {err}");
+                }
+            }
+
+            let mut saved = SavedState::load();
+            saved.warnings = WARNINGS
+                .iter()
+                .zip(state.warnings)
+                .filter(|(_, ticked)| *ticked)
+                .map(|((name, ..), _)| name.to_string())
+                .collect();
+            saved.save();
+        },
+        Message::ToggleCompact => {
+            state.compact = !state.compact;
+            let size = if state.compact {
+                state.expanded_size = state.window_size;
+                compact_window_size(state.font_size, state.ui_scale)
+            } else {
+                state
+                    .expanded_size
+                    .take()
+                    .unwrap_or_else(|| window_size(state.font_size, state.ui_scale))
+            };
+
+            let mut saved = SavedState::load();
+            saved.compact = state.compact;
+            saved.save();
+
+            return window::get_latest().and_then(move |id| window::resize(id, size));
+        },
+        Message::CheckConfig => {
+            let modified = state.sources_modified();
+            if modified.is_some() && modified != state.config_modified {
+                state.config_modified = modified;
+                match state.reload_config() {
+                    Ok(()) => return Task::done(Message::ConfigReloaded),
+                    Err(err) => state.config_error = Some(err),
+                }
+            }
+        },
+        Message::ConfigReloaded => {
+            state.reloaded_at = Some(Instant::now());
+        },
+        Message::Tick(now) => {
+            if state.restart_armed.is_some_and(|at| now.duration_since(at) >= RESTART_CONFIRM) {
+                state.restart_armed = None;
+            }
+            state.tick_rosh(now);
+            state.tick_cooldowns(now);
+            match &mut state.state {
+                TimerState::CountingDown(start_time) => {
+                    let elapsed = now.duration_since(*start_time).mul_f32(state.speed);
+                    let remaining = state.countdown.saturating_sub(elapsed);
+                    state.current_display = round_up_secs(remaining);
+
+                    // Whatever the tick ran past the horn is already game time
+                    if remaining.is_zero() {
+                        state.state = TimerState::Running {
+                            base_time: elapsed.saturating_sub(state.countdown),
+                            last_start: now,
+                        };
+                        state.sound_horn();
+                    }
+                    state.check_triggers_at(now);
+                },
+                TimerState::Running { base_time, last_start } => {
+                    let elapsed =
+                        *base_time + now.saturating_duration_since(*last_start).mul_f32(state.speed);
+                    state.current_display = elapsed;
+                    state.check_triggers_at(now);
+
+                    // Practice loop: start over as if Start had been pressed right at the end,
+                    // so the time the tick ran over isn't lost on every lap
+                    if let Some(end) = state.loop_end.filter(|end| elapsed >= *end) {
+                        let task = handle_message(state, Message::StartRestart);
+                        state.backdate(elapsed - end);
+                        return task;
+                    }
+                },
+                TimerState::Paused(elapsed) => {
+                    state.current_display = *elapsed;
+                },
+                _ => {}
+            }
+        },
+    }
+
+    Task::none()
+}
+
+/// What the built-in warning at this index of `WARNINGS` is called when it
+/// plays, e.g. "Power in 10".
+fn warning_name(index: usize) -> String {
+    format!("{} in {WARNING_LEAD_SECS}", WARNINGS[index].1)
+}
+
+/// The config's `title`, else the one from `--title`, else the app's name.
+fn window_title(state: &TimerApp) -> String {
+    state
+        .config_title
+        .as_deref()
+        .or(state.title.as_deref())
+        .unwrap_or(DEFAULT_TITLE)
+        .to_string()
+}
+
+/// Window size that fits the time display at `font_size` without clipping,
+/// with everything drawn `scale` times larger.
+fn window_size(font_size: f32, scale: f32) -> Size {
+    // "00:00" is roughly three font sizes wide, plus padding and the A-/A+ buttons
+    let extra = font_size - DEFAULT_FONT_SIZE;
+    Size::new((font_size * 3.0 + 100.0).max(200.0), 560.0 + extra * 2.1) * scale
+}
+
+/// Window size for the clock-only view: just the time text and a margin.
+fn compact_window_size(font_size: f32, scale: f32) -> Size {
+    Size::new(font_size * 3.0 + 24.0, font_size * 1.4 + 24.0) * scale
+}
+
+fn resize_window(state: &TimerApp) -> Task<Message> {
+    let size = if state.compact {
+        compact_window_size(state.font_size, state.ui_scale)
+    } else {
+        window_size(state.font_size, state.ui_scale)
+    };
+    window::get_latest().and_then(move |id| window::resize(id, size))
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Whether a window at `position` would overlap any connected monitor, so a
+/// position saved on a since-unplugged monitor isn't restored off-screen.
+#[cfg(windows)]
+fn is_on_screen(position: Point, size: Size) -> bool {
+    use windows_sys::Win32::{
+        Foundation::RECT,
+        Graphics::Gdi::{MonitorFromRect, MONITOR_DEFAULTTONULL},
+    };
+
+    let rect = RECT {
+        left: position.x as i32,
+        top: position.y as i32,
+        right: (position.x + size.width) as i32,
+        bottom: (position.y + size.height) as i32,
+    };
+    // SAFETY: `rect` is a valid RECT that outlives the call
+    !unsafe { MonitorFromRect(&rect, MONITOR_DEFAULTTONULL) }.is_null()
+}
+
+/// Monitor layout isn't available before the window opens on other
+/// platforms, so the saved position is trusted as long as it is sane.
+#[cfg(not(windows))]
+fn is_on_screen(position: Point, _size: Size) -> bool {
+    position.x.is_finite() && position.y.is_finite()
+}
+
+fn window_level(always_on_top: bool) -> Level {
+    if always_on_top {
+        Level::AlwaysOnTop
+    } else {
+        Level::Normal
+    }
+}
+
+// VIEW FUNCTION
+fn view(state: &TimerApp) -> iced::Element<'_, Message> {
+    let time_text = state.clock_text();
+
+    // Just the clock; right-click (or C) brings the controls back
+    if state.compact {
+        let time = widget::text(time_text).size(state.font_size).color_maybe(state.flash_tint());
+        return widget::mouse_area(widget::container(time).center(iced::Length::Fill))
+            .on_right_press(Message::ToggleCompact)
+            .into();
+    }
+
+    // Start/Restart button logic
+    let start_label = match state.state {
+        TimerState::Idle => "Start",
+        _ if state.restart_confirming() => "Confirm restart?",
+        _ => "Restart",
+    };
+
+    let start_restart_button = widget::button(start_label)
+        .on_press(Message::RestartPressed)
+        .padding(10);
+
+    // Pause/Resume button logic
+    let pause_resume_button = match state.state {
+        TimerState::Running{base_time:_, last_start:_} => Some(widget::button("Pause")
+            .on_press(Message::PauseResume)
+            .padding(10)),
+        TimerState::Paused(_) => Some(widget::button("Resume")
+            .on_press(Message::PauseResume)
+            .padding(10)),
+        _ => None,
+    };
+
+
+    let pick_list = widget::PickList::new(
+        state.filtered_files(),
+        state.highlighted_file.clone().or_else(|| state.selected_file.clone()).map(ConfigFile),
+        |file| Message::LoadYaml(file.0),
+    )
+        .placeholder("Select Strategy File");
+
+    let mut buttons = widget::row![start_restart_button];
+    if let Some(btn) = pause_resume_button {
+        buttons = buttons.push(btn);
+    }
+    // Rehearse a plan by stepping through the callouts while paused
+    if matches!(state.state, TimerState::Paused(_)) {
+        buttons = buttons.push(
+            widget::button("Next")
+                .on_press_maybe(state.next_callout_millis().map(|_| Message::StepToNext))
+                .padding(10),
+        );
+    }
+
+    // Nudge buttons for syncing with the in-game clock
+    let adjust_buttons = [-10, -1, 1, 10]
+        .into_iter()
+        .fold(widget::row![].spacing(2), |row, secs| {
+            row.push(
+                widget::button(widget::text(format!("{secs:+}")).size(12))
+                    .on_press(Message::AdjustTime(secs))
+                    .padding(3),
+            )
+        })
+        .push(
+            widget::button(widget::text("Undo").size(12))
+                .on_press_maybe((!state.adjustments.is_empty()).then_some(Message::UndoAdjust))
+                .padding(3),
+        );
+
+    // Jump straight to a typed-in game time, e.g. when launched mid-match
+    let sync_time = parse_clock(&state.sync_input);
+    let mut sync_row = widget::row![
+        widget::text_input("m:ss", &state.sync_input)
+            .on_input(Message::SyncInputChanged)
+            .on_submit_maybe(sync_time.map(Message::SyncTo))
+            .size(12)
+            .width(60),
+        widget::button(widget::text("Sync").size(12))
+            .on_press_maybe(sync_time.map(Message::SyncTo))
+            .padding(3),
+        widget::button(widget::text("Copy").size(12))
+            .on_press_maybe(state.chat_time().map(|_| Message::CopyTime))
+            .padding(3),
+    ]
+        .spacing(4)
+        .align_y(iced::Alignment::Center);
+    match state.copied_notice() {
+        Some(Ok(time)) => sync_row = sync_row.push(widget::text(format!("Copied {time}")).size(12)),
+        Some(Err(err)) => {
+            sync_row = sync_row.push(
+                widget::text(format!("Copy failed: {err}"))
+                    .size(12)
+                    .color(Color::from_rgb(0.9, 0.2, 0.2)),
+            );
+        }
+        None => {}
+    }
+
+    let volume_slider = widget::slider(0.0..=1.0, state.volume, Message::VolumeChanged)
+        .step(0.01);
+
+    let mute_button = widget::button(if state.muted { "Unmute" } else { "Mute" })
+        .on_press(Message::ToggleMute)
+        .padding(5);
+
+    let audio_device_picker = widget::pick_list(
+        state.audio_devices.as_slice(),
+        Some(state.audio.device().unwrap_or(DEFAULT_AUDIO_DEVICE).to_string()),
+        Message::SelectAudioDevice,
+    );
+
+    // Fast-forward for checking a config's timing; the current speed is the disabled one
+    let speed_row = SPEEDS.iter().fold(
+        widget::row![widget::text("Speed")].spacing(5).align_y(iced::Alignment::Center),
+        |row, &speed| {
+            row.push(
+                widget::button(widget::text(format!("{speed}x")).size(12))
+                    .on_press_maybe((speed != state.speed).then_some(Message::SetSpeed(speed)))
+                    .padding(4),
+            )
+        },
+    );
+
+    let opacity_slider = widget::slider(MIN_OPACITY..=1.0, state.opacity, Message::SetOpacity)
+        .step(0.05);
+
+    // Built-in heads-ups that work with any config
+    let warning_row = WARNINGS.iter().enumerate().fold(
+        widget::row![widget::text(format!("Warn {WARNING_LEAD_SECS}s before")).size(12)]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+        |row, (index, (_, label, ..))| {
+            row.push(
+                widget::checkbox(*label, state.warnings[index])
+                    .on_toggle(move |_| Message::ToggleWarning(index))
+                    .text_size(12)
+                    .size(14)
+                    .spacing(4),
+            )
+        },
+    );
+
+    // Roshan respawn window, counting down to the earliest and latest spawn
+    let rosh_row = match &state.rosh_state {
+        Some(rosh) => widget::row![
+            widget::text(format!(
+                "Rosh {} / {}",
+                format_clock(ROSH_MIN_RESPAWN.saturating_sub(rosh.elapsed)),
+                format_clock(ROSH_MAX_RESPAWN.saturating_sub(rosh.elapsed))
+            )),
+            widget::button("Clear").on_press(Message::RoshClear).padding(5),
+        ],
+        None => widget::row![
+            widget::button("Rosh Killed").on_press(Message::RoshKilled).padding(5),
+        ],
+    }
+        .spacing(5)
+        .align_y(iced::Alignment::Center);
+
+    let cooldown_buttons = state.cooldown_presets.iter().enumerate().fold(
+        widget::row![].spacing(2),
+        |row, (index, preset)| {
+            row.push(
+                widget::button(widget::text(&preset.label).size(12))
+                    .on_press(Message::StartCooldown(index))
+                    .padding(3),
+            )
+        },
+    );
+    let cooldown_list = state.cooldowns.iter().enumerate().fold(
+        widget::column![],
+        |list, (index, cooldown)| {
+            let line = if cooldown.remaining.is_zero() {
+                format!("{} ready", cooldown.label)
+            } else {
+                format!("{} {}", cooldown.label, format_clock(round_up_secs(cooldown.remaining)))
+            };
+            list.push(
+                widget::row![
+                    widget::text(line).size(12),
+                    widget::button(widget::text("×").size(10))
+                        .on_press(Message::RemoveCooldown(index))
+                        .padding(1),
+                ]
+                    .spacing(4)
+                    .align_y(iced::Alignment::Center),
+            )
+        },
+    );
+
+    let font_buttons = widget::row![
+        widget::button(widget::text("A-").size(12))
+            .on_press(Message::SetFontSize(state.font_size - FONT_SIZE_STEP))
+            .padding(3),
+        widget::button(widget::text("A+").size(12))
+            .on_press(Message::SetFontSize(state.font_size + FONT_SIZE_STEP))
+            .padding(3),
+        widget::button(widget::text("Min").size(12))
+            .on_press(Message::ToggleCompact)
+            .padding(3),
+    ]
+        .spacing(2);
+
+    let idle = matches!(state.state, TimerState::Idle);
+    let mut content = widget::column![
+        widget::row![
+            widget::text(time_text).size(state.font_size).color_maybe(state.flash_tint()),
+            font_buttons
+        ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center)
+    ];
+    if let Some(progress) = state.callout_progress() {
+        content = content.push(widget::progress_bar(0.0..=1.0, progress).height(6));
+    }
+    if !idle {
+        content = content.push(match state.next_event() {
+            Some((left, name)) => widget::text(format!("{name} in {}", format_clock(left)))
+                .size(state.font_size * NEXT_EVENT_SCALE),
+            None => widget::text("Nothing else scheduled").size(12),
+        });
+    }
+    if state.wall_clock && matches!(state.state, TimerState::Idle | TimerState::Paused(_)) {
+        let now = chrono::Local::now().format("%H:%M:%S");
+        content = content.push(widget::text(format!("Local time {now}")).size(12));
+    }
+    if let Some((day, left)) = state.day_phase() {
+        let (phase, next, color) = if day {
+            ("Day", "night", DAY_COLOR)
+        } else {
+            ("Night", "day", NIGHT_COLOR)
+        };
+        let left = format_game_clock(Duration::from_secs(left.unsigned_abs()));
+        content = content.push(
+            widget::text(format!("{phase}, {next} in {left}")).size(12).color(color),
+        );
+    }
+    if let Some(name) = &state.last_callout {
+        content = content.push(widget::text(format!("Last: {name}")).size(12));
+    }
+    if let Some(name) = state.audio.alert() {
+        content = content.push(
+            widget::row![
+                widget::text(format!("Alert: {name}")).size(12),
+                widget::button(widget::text("Dismiss").size(12))
+                    .on_press(Message::DismissAlert)
+                    .padding(3),
+            ]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        );
+    }
+    // Offered after a crash until the timer is started or the offer dismissed
+    if let Some(recovery) = state.recovery.as_ref().filter(|_| idle) {
+        let millis = recovery.game_millis_now();
+        let sign = if millis < 0 { "-" } else { "" };
+        let clock = format_game_clock(Duration::from_millis(millis.unsigned_abs()));
+        content = content.push(
+            widget::row![
+                widget::text(format!("Resume game at {sign}{clock}?")).size(12),
+                widget::button(widget::text("Resume").size(12))
+                    .on_press(Message::ResumeRecovery)
+                    .padding(3),
+                widget::button(widget::text("Dismiss").size(12))
+                    .on_press(Message::DismissRecovery)
+                    .padding(3),
+            ]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        );
+    }
+    content = content.push(buttons);
+    if !matches!(state.state, TimerState::Idle) {
+        content = content.push(adjust_buttons);
+    }
+    content = content.push(sync_row);
+    // Plays the callout picked on the timeline, or the first one, so a freshly
+    // edited config can be checked
+    let test_callout = state
+        .selected_callout
+        .filter(|millis| state.audio_map.contains_key(millis))
+        .or_else(|| state.audio_map.keys().next().copied());
+    let test_button = widget::button("Test")
+        .on_press_maybe(test_callout.map(Message::TestSound))
+        .padding(5);
+    let edit_button = widget::button(if state.editor.open { "Done" } else { "Edit" })
+        .on_press_maybe(state.selected_file.as_ref().map(|_| Message::Editor(EditorAction::Toggle)))
+        .padding(5);
+    content = content.push(
+        widget::text_input("Filter files", &state.file_filter)
+            .on_input(Message::FilterChanged)
+            .size(12),
+    );
+    content = content.push(widget::row![pick_list, test_button, edit_button].spacing(5));
+    if state.highlighted_file.is_some() {
+        let hint = widget::text("Enter loads it, Page Up/Page Down keep stepping").size(12);
+        content = content.push(hint);
+    }
+    if state.editor.open && state.selected_file.is_some() {
+        content = content.push(editor_panel(state));
+    }
+    if state.selected_file.is_some() {
+        // Layer more files over the selected one, e.g. rune timings plus a team plan
+        let mergeable: Vec<ConfigFile> = state
+            .yaml_files
+            .iter()
+            .filter(|file| {
+                state.selected_file.as_ref() != Some(&file.0) && !state.merged_files.contains(&file.0)
+            })
+            .cloned()
+            .collect();
+        content = content.push(
+            widget::pick_list(mergeable, None::<ConfigFile>, |file| Message::MergeYaml(file.0))
+                .placeholder("Add file"),
+        );
+        if !state.merged_files.is_empty() {
+            let names: Vec<String> =
+                state.merged_files.iter().map(|file| ConfigFile(file.clone()).to_string()).collect();
+            content = content.push(
+                widget::row![
+                    widget::text(format!("Merged: {}", names.join(", "))).size(12),
+                    widget::button(widget::text("Clear").size(12))
+                        .on_press(Message::ClearMerged)
+                        .padding(3),
+                ]
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center),
+            );
+        }
+        // A file that failed to load only shows its error, further down
+        if state.config_loaded {
+            let mut summary = widget::row![widget::text(state.config_summary()).size(12)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center);
+            if !matches!(state.state, TimerState::Idle) {
+                summary = summary.push(
+                    widget::button(widget::text("Apply now").size(12))
+                        .on_press(Message::ApplyConfigNow)
+                        .padding(3),
+                );
+            }
+            content = content.push(summary);
+        }
+    }
+    if !state.profiles.is_empty() {
+        content = content.push(widget::pick_list(
+            state.profiles.as_slice(),
+            state.selected_profile.clone(),
+            Message::SelectProfile,
+        ));
+    }
+
+    if !state.audio_map.is_empty() {
+        let timeline = Timeline {
+            marks: state.audio_map.keys().copied().collect(),
+            selected: state.selected_callout,
+            now: state.game_millis(),
+            start: -(state.countdown.as_millis() as i64),
+            on_select: Message::SelectCallout,
+        };
+        content = content.push(widget::canvas(timeline).width(iced::Length::Fill).height(28));
+    }
+    if state.reloaded_at.is_some_and(|at| at.elapsed() < RELOAD_FLASH) {
+        content = content.push(widget::text("Config reloaded").size(12));
+    }
+    let upcoming = state.upcoming_callouts();
+    if !upcoming.is_empty() {
+        let list = upcoming.into_iter().fold(widget::column![], |list, (second, left, entry)| {
+            let line = format!("{} in {}", entry.name(), format_clock(left));
+            list.push(
+                widget::row![
+                    widget::checkbox("", entry.enabled)
+                        .on_toggle(move |_| Message::ToggleCallout(second))
+                        .size(12)
+                        .spacing(0),
+                    widget::button(widget::text("▶").size(10))
+                        .on_press(Message::TestSound(second))
+                        .padding(1),
+                    widget::text(line)
+                        .size(12)
+                        .color_maybe((!entry.enabled).then_some(DISABLED_COLOR)),
+                ]
+                    .spacing(4)
+                    .align_y(iced::Alignment::Center),
+            )
+        });
+        content = content.push(widget::scrollable(list).height(60));
+    }
+    if !state.audio.has_output() {
+        content = content.push(
+            widget::text("No audio device — callouts muted")
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.2, 0.2)),
+        );
+    }
+    if let Some(err) = &state.config_error {
+        content = content.push(
+            widget::text(err)
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.2, 0.2)),
+        );
+    }
+
+    content
+        .push(
+            widget::row![volume_slider, mute_button]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .push(
+            widget::row![widget::text("Opacity"), opacity_slider]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .push(speed_row)
+        .push(widget::pick_list(AppTheme::ALL, Some(state.theme), Message::SetTheme))
+        .push(audio_device_picker)
+        .push(
+            widget::checkbox("Always on top", state.always_on_top)
+                .on_toggle(|_| Message::ToggleAlwaysOnTop),
+        )
+        .push(warning_row)
+        .push(rosh_row)
+        .push(cooldown_buttons)
+        .push(cooldown_list)
+        .padding(12)
+        .into()
+}
+
+/// Inputs for adding or removing one callout, and saving the changes.
+fn editor_panel(state: &TimerApp) -> iced::Element<'_, Message> {
+    let editor = &state.editor;
+    let second = editor.second();
+    let inputs = widget::row![
+        widget::text_input("m:ss", &editor.time)
+            .on_input(|time| Message::Editor(EditorAction::TimeChanged(time)))
+            .size(12)
+            .width(50),
+        widget::text_input("sound.wav", &editor.file)
+            .on_input(|file| Message::Editor(EditorAction::FileChanged(file)))
+            .size(12),
+        widget::text_input("Label", &editor.label)
+            .on_input(|label| Message::Editor(EditorAction::LabelChanged(label)))
+            .size(12),
+    ]
+        .spacing(4);
+
+    let idle = matches!(state.state, TimerState::Idle);
+    let exists = second.is_some_and(|second| state.is_written_at(i64::from(second) * 1000));
+    let buttons = widget::row![
+        widget::button(widget::text("Add").size(12))
+            .on_press_maybe(second.map(|_| Message::Editor(EditorAction::Add)))
+            .padding(3),
+        widget::button(widget::text("Remove").size(12))
+            .on_press_maybe(exists.then_some(Message::Editor(EditorAction::Remove)))
+            .padding(3),
+        widget::button(widget::text("Save").size(12))
+            .on_press_maybe(
+                (idle && editor.has_edits()).then_some(Message::Editor(EditorAction::Save)),
+            )
+            .padding(3),
+    ]
+        .spacing(4);
+
+    let save_as = widget::row![
+        widget::text_input("new-plan.yaml", &editor.save_as)
+            .on_input(|name| Message::Editor(EditorAction::SaveAsChanged(name)))
+            .on_submit(Message::Editor(EditorAction::SaveAs))
+            .size(12),
+        widget::button(widget::text("Save as").size(12))
+            .on_press(Message::Editor(EditorAction::SaveAs))
+            .padding(3),
+    ]
+        .spacing(4)
+        .align_y(iced::Alignment::Center);
+
+    let mut panel = widget::column![inputs, buttons, save_as].spacing(4);
+    if !idle && editor.has_edits() {
+        panel = panel.push(widget::text("Stop the timer to save").size(12));
+    }
+
+    // Sound library: ▶ previews a sound, clicking its name puts it in the file input
+    let mut matches = editor.library_matches();
+    let library = matches.by_ref().take(LIBRARY_LIMIT).fold(widget::column![], |list, path| {
+        list.push(
+            widget::row![
+                widget::button(widget::text("▶").size(10))
+                    .on_press(Message::Editor(EditorAction::Preview(path.to_string())))
+                    .padding(1),
+                widget::button(widget::text(path).size(12))
+                    .on_press(Message::Editor(EditorAction::Pick(path.to_string())))
+                    .style(widget::button::text)
+                    .padding(1),
+            ]
+                .spacing(4)
+                .align_y(iced::Alignment::Center),
+        )
+    });
+    let more = matches.count();
+    let library = if more > 0 {
+        library.push(widget::text(format!("{more} more, refine the search")).size(12))
+    } else {
+        library
+    };
+    panel = panel.push(
+        widget::text_input("Search sounds", &editor.query)
+            .on_input(|query| Message::Editor(EditorAction::QueryChanged(query)))
+            .size(12),
+    );
+    panel.push(widget::scrollable(library).height(80)).into()
+}
+
+/// Rounds the countdown up to whole seconds, so it only reads zero at the
+/// horn itself and "-0:01" is the last second before it.
+fn round_up_secs(duration: Duration) -> Duration {
+    Duration::from_secs(duration.as_secs() + u64::from(duration.subsec_nanos() > 0))
+}
+
+/// Parses a game time typed as `m:ss`, e.g. `5:30`.
+fn parse_clock(input: &str) -> Option<Duration> {
+    let (minutes, seconds) = input.trim().split_once(':')?;
+    if seconds.len() != 2 {
+        return None;
+    }
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: u64 = seconds.parse().ok()?;
+    if seconds >= 60 {
+        return None;
+    }
+    Some(Duration::from_secs(minutes.checked_mul(60)? + seconds))
+}
+
+/// `n` spelled out in English, e.g. "forty-five".
+fn number_words(n: u64) -> String {
+    const ONES: [&str; 20] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] =
+        ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        20..=99 if n % 10 == 0 => TENS[(n / 10) as usize].to_string(),
+        20..=99 => format!("{}-{}", TENS[(n / 10) as usize], ONES[(n % 10) as usize]),
+        _ if n % 100 == 0 && n < 1000 => format!("{} hundred", ONES[(n / 100) as usize]),
+        100..=999 => format!("{} hundred {}", ONES[(n / 100) as usize], number_words(n % 100)),
+        // Nobody plays a game this long, so digits will do
+        _ => n.to_string(),
+    }
+}
+
+/// Whole seconds at or after game millisecond `millis`.
+fn ceil_secs(millis: i64) -> i64 {
+    -(-millis).div_euclid(1000)
+}
+
+/// A game millisecond as the editor's `m:ss` or `-m:ss`, dropping any fraction.
+fn format_editor_time(millis: i64) -> String {
+    let secs = (millis / 1000).unsigned_abs();
+    let sign = if millis <= -1000 { "-" } else { "" };
+    format!("{sign}{}:{:02}", secs / 60, secs % 60)
+}
+
+/// A game millisecond as seconds for messages, e.g. `180` or `180.5`.
+fn format_millis(millis: i64) -> String {
+    if millis % 1000 == 0 {
+        (millis / 1000).to_string()
+    } else {
+        (millis as f64 / 1000.0).to_string()
+    }
+}
+
+fn format_game_clock(duration: Duration) -> String {
+    format!("{}:{:02}", duration.as_secs() / 60, duration.as_secs() % 60)
+}
+
+fn format_clock(duration: Duration) -> String {
+    format!("{:02}:{:02}", duration.as_secs() / 60, duration.as_secs() % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path of a short silent WAV named `name`, written on first use, so
+    /// configs pass the load-time check that their sounds play.
+    fn sound(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("dota-timer-tests-{}", std::process::id()));
+        let path = dir.join(format!("{name}.wav"));
+        if !path.exists() {
+            fs::create_dir_all(&dir).unwrap();
+            let samples = 441u32;
+            let mut wav = Vec::new();
+            wav.extend_from_slice(b"RIFF");
+            wav.extend_from_slice(&(36 + samples * 2).to_le_bytes());
+            wav.extend_from_slice(b"WAVEfmt ");
+            wav.extend_from_slice(&16u32.to_le_bytes());
+            wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+            wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+            wav.extend_from_slice(&44_100u32.to_le_bytes());
+            wav.extend_from_slice(&88_200u32.to_le_bytes());
+            wav.extend_from_slice(&2u16.to_le_bytes());
+            wav.extend_from_slice(&16u16.to_le_bytes());
+            wav.extend_from_slice(b"data");
+            wav.extend_from_slice(&(samples * 2).to_le_bytes());
+            wav.resize(wav.len() + samples as usize * 2, 0);
+            // Written aside and renamed, as tests running at once may share it
+            let partial = dir.join(format!("{name}.{:?}.part", std::thread::current().id()));
+            fs::write(&partial, wav).unwrap();
+            fs::rename(&partial, &path).unwrap();
+        }
+        path.to_string_lossy().into_owned()
+    }
+
+    /// A stopped timer with `yaml` loaded as its config.
+    fn app_with(yaml: &str) -> TimerApp {
+        let mut app = TimerApp::new();
+        app.apply_config(Config::parse("test.yaml", yaml).expect("test config parses"));
+        app
+    }
+
+    /// Jumps the clock to game millisecond `millis` and returns the instant
+    /// it read that at, for checking triggers from.
+    fn jump(app: &mut TimerApp, millis: i64) -> Instant {
+        app.jump_to(millis, false);
+        match app.state {
+            TimerState::CountingDown(start) => {
+                start + app.countdown - Duration::from_millis(millis.unsigned_abs())
+            }
+            TimerState::Running { last_start, .. } => last_start,
+            _ => unreachable!("jump_to leaves the clock running"),
+        }
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn the_countdown_only_counts_down_until_the_horn() {
+        let mut app = TimerApp::new();
+        let _ = handle_message(&mut app, Message::StartRestart);
+        let TimerState::CountingDown(start) = app.state else {
+            panic!("Start begins the countdown");
+        };
+        let mut shown = app.current_display;
+        assert_eq!(shown, PREGAME_COUNTDOWN);
+
+        let mut horns = 0;
+        let ticks = PREGAME_COUNTDOWN.as_millis() as u64 / 250 + 8;
+        for tick in 0..=ticks {
+            let counting = matches!(app.state, TimerState::CountingDown(_));
+            let _ = handle_message(&mut app, Message::Tick(start + ms(tick * 250)));
+            match app.state {
+                TimerState::CountingDown(_) => {
+                    assert!(app.current_display <= shown, "went up at tick {tick}");
+                    shown = app.current_display;
+                }
+                TimerState::Running { .. } => horns += usize::from(counting),
+                _ => panic!("ticks only count down and then run"),
+            }
+            if tick == 0 {
+                assert_eq!(app.current_display, PREGAME_COUNTDOWN, "no jump on the first tick");
+            }
+        }
+        assert_eq!(horns, 1);
+    }
+
+    #[test]
+    fn a_config_whose_sounds_all_play_loads_cleanly() {
+        let app = app_with(&format!("audio:\n  60: {}\n", sound("rune")));
+        assert_eq!(app.config_error, None);
+        assert_eq!(app.audio_map.len(), 1);
+    }
+
+    #[test]
+    fn a_missing_sound_is_reported_and_only_the_good_one_plays() {
+        let missing = std::env::temp_dir().join("dota-timer-tests-missing.wav");
+        let missing = missing.to_string_lossy().into_owned();
+        let mut app = app_with(&format!("audio:\n  60: {}\n  61: {missing}\n", sound("rune")));
+        assert_eq!(app.config_error, Some(format!("Cannot play: {missing}")));
+
+        let at = jump(&mut app, 59_000);
+        app.check_triggers_at(at + ms(3_000));
+        assert_eq!(app.audio.played, [sound("rune")]);
+    }
+
+    #[test]
+    fn the_horn_and_callouts_either_side_of_it_play_in_order() {
+        let horn = sound("horn");
+        let mut app = app_with(&format!(
+            "horn_sound: {horn}\naudio:\n  -10: {}\n  0: {}\n",
+            sound("pregame"),
+            sound("start")
+        ));
+        let at = jump(&mut app, -11_000);
+        let _ = handle_message(&mut app, Message::Tick(at + ms(1_500)));
+        assert_eq!(app.audio.played, [sound("pregame")]);
+
+        // This tick lands 200ms past the horn, which the game clock keeps
+        let _ = handle_message(&mut app, Message::Tick(at + ms(11_200)));
+        assert_eq!(app.audio.played, [sound("pregame"), horn, sound("start")]);
+        // Give or take a millisecond of float rounding in the speed
+        let game = app.game_millis_at(at + ms(11_200)).unwrap();
+        assert!((199..=201).contains(&game), "game clock at {game}ms");
+    }
+
+    #[test]
+    fn clock_ignores_a_now_before_it_started() {
+        let mut app = TimerApp::new();
+        let at = jump(&mut app, 60_000);
+        assert_eq!(app.game_millis_at(at + ms(5_000)), Some(65_000));
+        // As if the clock were read with a time from before the jump
+        assert_eq!(app.game_millis_at(at.checked_sub(ms(5_000)).unwrap_or(at)), Some(60_000));
+    }
+
+    #[test]
+    fn stepping_over_a_callout_skips_it_and_stepping_back_replays_it() {
+        let mut app = app_with(&format!("audio:\n  60: {}\n", sound("rune")));
+        let at = jump(&mut app, 55_000);
+        app.check_triggers_at(at + ms(1_000));
+        assert!(app.audio.played.is_empty());
+
+        app.adjust_time(10);
+        app.check_triggers();
+        assert!(app.fired.contains(&Trigger::Callout(60_000)));
+        assert!(app.audio.played.is_empty(), "a skipped callout doesn't play");
+
+        app.adjust_time(-10);
+        assert!(!app.fired.contains(&Trigger::Callout(60_000)));
+        app.check_triggers_at(Instant::now() + ms(5_000));
+        assert_eq!(app.audio.played, [sound("rune")]);
+    }
+
+    #[test]
+    fn a_callout_plays_once_however_many_ticks_land_on_it() {
+        let mut app = app_with(&format!("audio:\n  60: {}\n", sound("rune")));
+        let at = jump(&mut app, 59_900);
+        for tick in 1..=5 {
+            app.check_triggers_at(at + ms(tick * 100));
+        }
+        assert_eq!(app.audio.played, [sound("rune")]);
+    }
+
+    #[test]
+    fn syncing_from_a_pause_resumes_everything_like_resume() {
+        let mut app = TimerApp::new();
+        jump(&mut app, 60_000);
+        let _ = handle_message(&mut app, Message::RoshKilled);
+        let _ = handle_message(&mut app, Message::StartCooldown(0));
+        let _ = handle_message(&mut app, Message::AdjustTime(10));
+        let _ = handle_message(&mut app, Message::PauseResume);
+        app.auto_paused = true;
+
+        let _ = handle_message(&mut app, Message::SyncTo(Duration::from_secs(300)));
+        let TimerState::Running { base_time, .. } = app.state else {
+            panic!("still paused after syncing");
+        };
+        assert_eq!(base_time, Duration::from_secs(300));
+        assert!(!app.auto_paused);
+        assert!(app.adjustments.is_empty());
+        assert!(app.rosh_state.as_ref().is_some_and(|rosh| rosh.resumed_at.is_some()));
+        assert!(app.cooldowns[0].last_tick.is_some());
+    }
+
+    #[test]
+    fn pausing_freezes_the_clock_roshan_and_cooldowns() {
+        let mut app = TimerApp::new();
+        jump(&mut app, 60_000);
+        let _ = handle_message(&mut app, Message::RoshKilled);
+        let _ = handle_message(&mut app, Message::StartCooldown(0));
+        let _ = handle_message(&mut app, Message::PauseResume);
+        let game = app.game_millis();
+        let rosh = app.rosh_state.as_ref().map(|rosh| rosh.elapsed);
+        let cooldown = app.cooldowns[0].remaining;
+
+        let _ = handle_message(&mut app, Message::Tick(Instant::now() + ms(30_000)));
+        assert_eq!(app.game_millis(), game);
+        assert_eq!(app.rosh_state.as_ref().map(|rosh| rosh.elapsed), rosh);
+        assert_eq!(app.cooldowns[0].remaining, cooldown);
+    }
+
+    /// An empty folder of its own under the temp directory for test `name`.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("dota-timer-tests-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn config_files_are_found_and_missing_folders_skipped() {
+        let dir = temp_dir("discovery");
+        fs::write(dir.join("turbo.yaml"), "audio: {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "not a config").unwrap();
+
+        let files = get_yaml_files(&[dir.join("no-such-folder"), dir.clone()]);
+        assert_eq!(files, [dir.join("turbo.yaml").to_string_lossy()]);
+    }
+
+    #[test]
+    fn an_unsupported_format_is_a_warning_not_a_panic() {
+        let file = temp_dir("formats").join("callout.aiff");
+        fs::write(&file, b"FORM").unwrap();
+        let file = file.to_string_lossy().into_owned();
+        let mut app = app_with(&format!("audio:\n  60: {file}\n  61: {}\n", sound("rune")));
+        assert_eq!(app.config_error, Some(format!("Cannot play: {file}")));
+        assert!(audio::check_playable(&file).unwrap_err().to_string().contains("(.aiff)"));
+
+        let at = jump(&mut app, 59_000);
+        app.check_triggers_at(at + ms(3_000));
+        assert_eq!(app.audio.played, [sound("rune")]);
+    }
+
+    #[test]
+    fn relative_sounds_are_found_next_to_a_config_in_a_subfolder() {
+        let dir = temp_dir("relative");
+        let sounds = dir.join("configs").join("sounds");
+        fs::create_dir_all(&sounds).unwrap();
+        fs::copy(sound("rune"), sounds.join("rune.wav")).unwrap();
+        let file = dir.join("configs").join("turbo.yaml");
+        fs::write(&file, "audio:\n  60: sounds/rune.wav\n").unwrap();
+
+        let mut app = TimerApp::new();
+        app.load_file(file.to_string_lossy().into_owned());
+        assert_eq!(app.config_error, None);
+        let entry = &app.audio_map[&60_000];
+        assert_eq!(entry.file.as_deref().map(Path::new), Some(sounds.join("rune.wav").as_path()));
+    }
+
+    #[test]
+    fn editing_a_callout_with_a_lead_keys_on_its_written_time() {
+        let dir = std::env::temp_dir().join(format!("dota-timer-tests-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("lead.yaml");
+        let yaml = format!("lead_time_secs: 5\naudio:\n  60: {}\n", sound("rune"));
+        fs::write(&file, yaml).unwrap();
+        let mut app = TimerApp::new();
+        app.load_file(file.to_string_lossy().into_owned());
+        assert!(app.audio_map.contains_key(&55_000));
+
+        let _ = handle_message(&mut app, Message::SelectCallout(55_000));
+        assert_eq!(app.editor.time, "1:00");
+        let _ = handle_message(&mut app, Message::Editor(EditorAction::Remove));
+        assert!(app.audio_map.is_empty());
+
+        app.editor.time = "1:30".to_string();
+        app.editor.file = sound("rune");
+        let _ = handle_message(&mut app, Message::Editor(EditorAction::Add));
+        assert_eq!(app.audio_map.keys().collect::<Vec<_>>(), [&85_000]);
+    }
+
+    #[test]
+    fn page_keys_highlight_a_file_and_enter_loads_it() {
+        let mut app = TimerApp::new();
+        app.yaml_files = ["a.yaml", "b.yaml"].map(|file| ConfigFile(file.to_string())).into();
+        for step in [1, 1, 1, -1] {
+            let _ = handle_message(&mut app, Message::CycleConfig(step));
+        }
+        assert_eq!(app.highlighted_file.as_deref(), Some("b.yaml"));
+        assert_eq!(app.selected_file, None);
+
+        let _ = handle_message(&mut app, Message::RestartPressed);
+        assert_eq!(app.selected_file.as_deref(), Some("b.yaml"));
+        assert_eq!(app.highlighted_file, None);
+        assert!(matches!(app.state, TimerState::Idle));
+    }
+
+    #[test]
+    fn a_valid_file_without_callouts_says_so() {
+        let app = app_with("audio: {}\n");
+        assert_eq!(app.config_error, None);
+        assert_eq!(app.config_summary(), "No callouts in this file");
+
+        let rule = format!("  - start: 60\n    interval: 60\n    sound: {}\n", sound("rune"));
+        let yaml = format!("repeat:\n{rule}");
+        assert_eq!(app_with(&yaml).config_summary(), "1 repeating callout");
+    }
+
+    #[test]
+    fn clock_style_keys_fire_before_and_after_the_horn() {
+        let mut app = app_with(&format!(
+            "audio:\n  \"-0:30\": {}\n  \"0:30\": {}\n",
+            sound("early"),
+            sound("late")
+        ));
+        let at = jump(&mut app, -31_000);
+        app.check_triggers_at(at + ms(1_500));
+        let at = jump(&mut app, 29_000);
+        app.check_triggers_at(at + ms(1_500));
+        assert_eq!(app.audio.played, [sound("early"), sound("late")]);
+    }
+
+    #[test]
+    fn save_as_still_has_the_callouts_whose_sounds_were_dropped() {
+        let missing = std::env::temp_dir().join("dota-timer-tests-missing.wav");
+        let missing = missing.to_string_lossy().into_owned();
+        let app = app_with(&format!("audio:\n  60: {}\n  61: {missing}\n", sound("rune")));
+        assert_eq!(app.audio_map.keys().collect::<Vec<_>>(), [&60_000]);
+        assert_eq!(app.loaded_audio.keys().collect::<Vec<_>>(), [&60_000, &61_000]);
+    }
+
+    #[test]
+    fn a_disabled_callout_never_reaches_the_player() {
+        let yaml = format!(
+            "audio:\n  60:\n    file: {}\n    enabled: false\n  61: {}\n",
+            sound("muted"),
+            sound("rune")
+        );
+        let mut app = app_with(&yaml);
+        let at = jump(&mut app, 59_000);
+        app.check_triggers_at(at + ms(1_500));
+        app.check_triggers_at(at + ms(2_500));
+        assert_eq!(app.audio.played, [sound("rune")]);
+    }
+
+    #[test]
+    fn the_next_event_counts_the_horn_and_ticked_warnings() {
+        let mut app = app_with(&format!("horn_sound: {}\n", sound("horn")));
+        jump(&mut app, -5_000);
+        assert_eq!(app.next_event(), Some((Duration::from_secs(5), "Horn".to_string())));
+
+        // Power runes are at 6:00, so warned of at 5:50
+        app.warnings[1] = true;
+        jump(&mut app, 345_000);
+        assert_eq!(app.next_event(), Some((Duration::from_secs(5), "Power in 10".to_string())));
+    }
+
+    #[test]
+    fn yml_configs_are_found_and_read_like_yaml() {
+        let dir = temp_dir("yml");
+        let file = dir.join("short.yml");
+        fs::write(&file, format!("audio:\n  \"1:00\": {}\n", sound("rune"))).unwrap();
+
+        let files = get_yaml_files(&[dir]);
+        assert_eq!(files, [file.to_string_lossy()]);
+        let mut app = TimerApp::new();
+        app.load_file(files[0].clone());
+        assert_eq!(app.config_error, None);
+        assert!(app.audio_map.contains_key(&60_000));
+    }
+
+    #[test]
+    fn a_callout_plays_at_its_own_volume_else_the_default_times_the_master() {
+        let (rune, horn) = (sound("rune"), sound("horn"));
+        let yaml = format!("audio:\n  60:\n    file: {rune}\n    volume: 0.2\n  61: {horn}\n");
+        let volumes = |app: &TimerApp| -> Vec<f32> {
+            app.audio_map.values().map(|entry| audio::entry_volume(entry, 0.5)).collect()
+        };
+        assert_eq!(volumes(&app_with(&yaml)), [0.1, 0.5]);
+        assert_eq!(volumes(&app_with(&format!("default_volume: 0.4\n{yaml}"))), [0.1, 0.2]);
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(feature = "overlay")]
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Browser page for an OBS browser source: polls `/state` and shows the clock
+/// on a transparent background.
+#[cfg(feature = "overlay")]
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Dota Timer</title>
+<style>
+  body { margin: 0; background: transparent; color: #fff; font: bold 48px sans-serif;
+         text-shadow: 0 0 4px #000; }
+  #next { font-size: 20px; }
+</style>
+</head>
+<body>
+<div id="clock">--:--</div>
+<div id="next"></div>
+<script>
+async function poll() {
+  try {
+    const state = await (await fetch("/state")).json();
+    document.getElementById("clock").textContent = state.clock;
+    document.getElementById("next").textContent =
+      state.next ? `${state.next.name} in ${state.next.in}s` : "";
+  } catch (err) {}
+}
+setInterval(poll, 250);
+poll();
+</script>
+</body>
+</html>
+"#;
+
+/// Optional local HTTP server exposing the timer to stream overlays. The app
+/// publishes a JSON snapshot after every update and the server thread hands
+/// out the latest one. Inert without the `overlay` feature.
+#[derive(Debug, Default)]
+pub struct Overlay {
+    #[cfg(feature = "overlay")]
+    snapshot: Option<Arc<Mutex<String>>>,
+}
+
+impl Overlay {
+    /// Serves on `127.0.0.1:port`; a port that can't be bound is logged and
+    /// the overlay stays off.
+    #[cfg(feature = "overlay")]
+    pub fn start(port: u16) -> Self {
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                eprintln!("Overlay server unavailable on port {port}: {err}");
+                return Self::default();
+            }
+        };
+
+        let snapshot = Arc::new(Mutex::new("{}".to_string()));
+        let shared = Arc::clone(&snapshot);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(err) = respond(stream, &shared) {
+                    eprintln!("Overlay request failed: {err}");
+                }
+            }
+        });
+        Self { snapshot: Some(snapshot) }
+    }
+
+    #[cfg(not(feature = "overlay"))]
+    pub fn start(port: u16) -> Self {
+        eprintln!("Ignoring overlay port {port}: built without the `overlay` feature");
+        Self {}
+    }
+
+    /// Replaces the served snapshot. `json` is only built while serving.
+    #[cfg(feature = "overlay")]
+    pub fn publish(&self, json: impl FnOnce() -> String) {
+        if let Some(snapshot) = &self.snapshot {
+            let json = json();
+            if let Ok(mut snapshot) = snapshot.lock() {
+                *snapshot = json;
+            }
+        }
+    }
+
+    #[cfg(not(feature = "overlay"))]
+    pub fn publish(&self, _json: impl FnOnce() -> String) {}
+}
+
+/// Answers one request: the page at `/`, the snapshot at `/state`.
+#[cfg(feature = "overlay")]
+fn respond(mut stream: TcpStream, snapshot: &Mutex<String>) -> std::io::Result<()> {
+    // A client that never sends its request mustn't hold up the others
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = match path {
+        "/" => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        "/state" => {
+            let json = snapshot.lock().map(|json| json.clone()).unwrap_or_default();
+            ("200 OK", "application/json", json)
+        }
+        _ => ("404 Not Found", "text/plain", "Not found".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Where a game in progress is checkpointed, next to the saved UI state.
+const RECOVERY_FILE: &str = ".dota2-timer-recovery.json";
+
+/// Oldest checkpoint still offered for resuming; past this the game has
+/// likely moved on too far for the clock to be trusted.
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    Countdown,
+    Running,
+    Paused,
+}
+
+/// A checkpoint of the running timer, so a crash doesn't lose the game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recovery {
+    pub phase: Phase,
+    /// Game clock in milliseconds when saved, negative during the countdown.
+    pub game_millis: i64,
+    #[serde(default)]
+    pub selected_file: Option<String>,
+    #[serde(default)]
+    pub merged_files: Vec<String>,
+    #[serde(default)]
+    pub selected_profile: Option<String>,
+    /// Wall-clock time of the checkpoint, in seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+impl Recovery {
+    /// The checkpoint left by a previous run, if it is recent enough to resume.
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(RECOVERY_FILE).ok()?;
+        let recovery: Self = serde_json::from_str(&contents).ok()?;
+        (recovery.age()? <= MAX_AGE).then_some(recovery)
+    }
+
+    /// How long ago the checkpoint was written.
+    pub fn age(&self) -> Option<Duration> {
+        let saved_at = UNIX_EPOCH + Duration::from_secs(self.saved_at);
+        SystemTime::now().duration_since(saved_at).ok()
+    }
+
+    /// The game clock now, assuming it kept running since the checkpoint.
+    pub fn game_millis_now(&self) -> i64 {
+        match self.phase {
+            Phase::Paused => self.game_millis,
+            Phase::Countdown | Phase::Running => {
+                let age = self.age().unwrap_or_default().as_millis() as i64;
+                self.game_millis + age
+            }
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.saved_at = now_secs();
+        let result = serde_json::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(RECOVERY_FILE, contents).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            eprintln!("Failed to save {RECOVERY_FILE}: {err}");
+        }
+    }
+
+    /// Removes the checkpoint, e.g. on a clean exit.
+    pub fn clear() {
+        if let Err(err) = fs::remove_file(RECOVERY_FILE) {
+            if err.kind() != ErrorKind::NotFound {
+                eprintln!("Failed to remove {RECOVERY_FILE}: {err}");
+            }
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
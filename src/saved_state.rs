@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Small JSON file next to the strategy configs remembering UI choices
+/// between launches.
+const STATE_FILE: &str = ".dota2-timer-state.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedState {
+    #[serde(default)]
+    pub selected_file: Option<String>,
+    #[serde(default = "default_always_on_top")]
+    pub always_on_top: bool,
+    /// Last window size and top-left position, in logical pixels.
+    #[serde(default)]
+    pub window_size: Option<(f32, f32)>,
+    #[serde(default)]
+    pub window_position: Option<(f32, f32)>,
+    /// Name of the chosen audio output; the system default when absent.
+    #[serde(default)]
+    pub audio_device: Option<String>,
+    /// Clock-only window mode.
+    #[serde(default)]
+    pub compact: bool,
+    /// Names of the built-in warnings ticked in the window.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+fn default_always_on_top() -> bool {
+    true
+}
+
+impl Default for SavedState {
+    fn default() -> Self {
+        Self {
+            selected_file: None,
+            always_on_top: default_always_on_top(),
+            window_size: None,
+            window_position: None,
+            audio_device: None,
+            compact: false,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl SavedState {
+    /// Reads the saved state, falling back to defaults if it is missing or corrupt.
+    pub fn load() -> Self {
+        fs::read_to_string(STATE_FILE)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(STATE_FILE, contents).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            eprintln!("Failed to save {STATE_FILE}: {err}");
+        }
+    }
+}
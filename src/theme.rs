@@ -0,0 +1,72 @@
+use iced::{theme::Palette, Color, Theme};
+use serde::Deserialize;
+use std::fmt;
+
+/// Built-in color themes selectable from the config or at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppTheme {
+    /// iced's own default look, following the system light/dark mode.
+    #[default]
+    Default,
+    Light,
+    /// Semi-transparent dark overlay with high-contrast text.
+    Dark,
+    /// Dark red and gold, after the game's own UI.
+    Dota,
+}
+
+impl AppTheme {
+    pub const ALL: [AppTheme; 4] = [
+        AppTheme::Default,
+        AppTheme::Light,
+        AppTheme::Dark,
+        AppTheme::Dota,
+    ];
+
+    pub fn theme(self) -> Theme {
+        match self {
+            AppTheme::Default => Theme::default(),
+            AppTheme::Light => Theme::Light,
+            AppTheme::Dark => Theme::custom(
+                "Dark overlay".to_string(),
+                Palette {
+                    background: Color::from_rgb(0.05, 0.05, 0.07),
+                    text: Color::WHITE,
+                    primary: Color::from_rgb(0.35, 0.55, 0.95),
+                    success: Color::from_rgb(0.3, 0.8, 0.45),
+                    danger: Color::from_rgb(0.95, 0.3, 0.3),
+                },
+            ),
+            AppTheme::Dota => Theme::custom(
+                "Dota".to_string(),
+                Palette {
+                    background: Color::from_rgb(0.11, 0.05, 0.04),
+                    text: Color::from_rgb(0.96, 0.87, 0.62),
+                    primary: Color::from_rgb(0.68, 0.16, 0.1),
+                    success: Color::from_rgb(0.45, 0.7, 0.25),
+                    danger: Color::from_rgb(0.9, 0.25, 0.15),
+                },
+            ),
+        }
+    }
+
+    /// Background alpha the theme is drawn with, before the opacity slider applies.
+    pub fn background_alpha(self) -> f32 {
+        match self {
+            AppTheme::Dark => 0.8,
+            _ => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for AppTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AppTheme::Default => "Default",
+            AppTheme::Light => "Light",
+            AppTheme::Dark => "Dark",
+            AppTheme::Dota => "Dota",
+        })
+    }
+}
@@ -0,0 +1,151 @@
+use iced::{
+    mouse,
+    widget::canvas::{self, event, Event, Frame, Geometry, Path, Stroke, Text},
+    Color, Point, Rectangle, Renderer, Size, Theme,
+};
+
+/// How far from a mark, in pixels, a click still selects it.
+const CLICK_TOLERANCE: f32 = 6.0;
+
+/// Horizontal timeline of every callout over the game, with a playhead at
+/// the current time. Clicking a mark selects that callout.
+pub struct Timeline<Message> {
+    /// Game milliseconds of the callouts, in order.
+    pub marks: Vec<i64>,
+    pub selected: Option<i64>,
+    /// The playhead, `None` while idle.
+    pub now: Option<i64>,
+    /// Game millisecond at the left edge, usually the start of the countdown.
+    pub start: i64,
+    pub on_select: fn(i64) -> Message,
+}
+
+impl<Message> Timeline<Message> {
+    /// The right edge: the last callout, or the playhead once it passes it,
+    /// so long games scale down to fit.
+    fn end(&self) -> i64 {
+        let last = self.marks.last().copied().unwrap_or(0).max(self.now.unwrap_or(0));
+        last.max(self.start + 1)
+    }
+
+    fn x(&self, millis: i64, width: f32) -> f32 {
+        let span = (self.end() - self.start) as f32;
+        (millis - self.start) as f32 / span * width
+    }
+
+    /// The mark nearest to `x` within `CLICK_TOLERANCE`.
+    fn mark_at(&self, x: f32, width: f32) -> Option<i64> {
+        self.marks
+            .iter()
+            .map(|millis| (*millis, (self.x(*millis, width) - x).abs()))
+            .filter(|(_, distance)| *distance <= CLICK_TOLERANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(millis, _)| millis)
+    }
+}
+
+impl<Message> canvas::Program<Message> for Timeline<Message> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (event::Status::Ignored, None);
+        };
+        let Some(position) = cursor.position_in(bounds) else {
+            return (event::Status::Ignored, None);
+        };
+        match self.mark_at(position.x, bounds.width) {
+            Some(millis) => (event::Status::Captured, Some((self.on_select)(millis))),
+            None => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let palette = theme.palette();
+        let mut frame = Frame::new(renderer, bounds.size());
+        let (width, height) = (bounds.width, bounds.height);
+        let middle = height / 2.0;
+
+        let faded = Color { a: 0.4, ..palette.text };
+        frame.stroke(
+            &Path::line(Point::new(0.0, middle), Point::new(width, middle)),
+            Stroke::default().with_color(faded).with_width(1.0),
+        );
+        // The horn, when the countdown is in view
+        if self.start < 0 {
+            let x = self.x(0, width);
+            frame.stroke(
+                &Path::line(Point::new(x, middle - 8.0), Point::new(x, middle + 8.0)),
+                Stroke::default().with_color(faded).with_width(2.0),
+            );
+        }
+
+        for millis in &self.marks {
+            let x = self.x(*millis, width);
+            let played = self.now.is_some_and(|now| now >= *millis);
+            let (color, size) = if self.selected == Some(*millis) {
+                (palette.success, 6.0)
+            } else if played {
+                (faded, 4.0)
+            } else {
+                (palette.primary, 4.0)
+            };
+            frame.fill_rectangle(
+                Point::new(x - size / 2.0, middle - size * 1.5),
+                Size::new(size, size * 3.0),
+                color,
+            );
+        }
+
+        if let Some(now) = self.now {
+            let x = self.x(now, width);
+            frame.stroke(
+                &Path::line(Point::new(x, 0.0), Point::new(x, height)),
+                Stroke::default().with_color(palette.danger).with_width(2.0),
+            );
+        }
+        frame.fill_text(Text {
+            content: format_minutes(self.end()),
+            position: Point::new(width, height),
+            color: faded,
+            size: 10.into(),
+            horizontal_alignment: iced::alignment::Horizontal::Right,
+            vertical_alignment: iced::alignment::Vertical::Bottom,
+            ..Text::default()
+        });
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &(),
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        match cursor.position_in(bounds) {
+            Some(position) if self.mark_at(position.x, bounds.width).is_some() => {
+                mouse::Interaction::Pointer
+            }
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
+
+/// The timeline's length label, e.g. `40:00`.
+fn format_minutes(millis: i64) -> String {
+    let secs = millis.max(0) / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,125 @@
+use iced::futures::Stream;
+#[cfg(feature = "tray")]
+use iced::futures::{channel::mpsc, SinkExt, StreamExt};
+use std::fmt;
+#[cfg(feature = "tray")]
+use tray_icon::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    Icon, TrayIcon, TrayIconBuilder,
+};
+
+/// Menu item ids and text, in menu order.
+#[cfg(feature = "tray")]
+const ITEMS: [(&str, &str, TrayAction); 3] = [
+    ("start_restart", "Start/Restart", TrayAction::StartRestart),
+    ("pause_resume", "Pause/Resume", TrayAction::PauseResume),
+    ("quit", "Quit", TrayAction::Quit),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "tray"), allow(dead_code))]
+pub enum TrayAction {
+    StartRestart,
+    PauseResume,
+    Quit,
+}
+
+/// Tray icon whose menu mirrors the main buttons, so the timer can be driven
+/// without the window on screen. Absent when the platform has no tray or the
+/// app was built without the `tray` feature.
+pub struct Tray {
+    #[cfg(feature = "tray")]
+    _icon: Option<TrayIcon>,
+}
+
+impl Tray {
+    #[cfg(feature = "tray")]
+    pub fn new() -> Self {
+        let icon = Self::build()
+            .map_err(|err| eprintln!("System tray unavailable: {err}"))
+            .ok();
+        Self { _icon: icon }
+    }
+
+    #[cfg(not(feature = "tray"))]
+    pub fn new() -> Self {
+        Self::none()
+    }
+
+    /// No icon at all, whatever the platform.
+    pub fn none() -> Self {
+        Self {
+            #[cfg(feature = "tray")]
+            _icon: None,
+        }
+    }
+
+    #[cfg(feature = "tray")]
+    fn build() -> Result<TrayIcon, Box<dyn std::error::Error>> {
+        // The Linux tray needs a GTK main loop, which iced doesn't run
+        if cfg!(target_os = "linux") {
+            return Err("not supported on Linux".into());
+        }
+
+        let menu = Menu::new();
+        for (id, text, action) in ITEMS {
+            if action == TrayAction::Quit {
+                menu.append(&PredefinedMenuItem::separator())?;
+            }
+            menu.append(&MenuItem::with_id(id, text, true, None))?;
+        }
+
+        Ok(TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("Dota 2 Timer")
+            .with_icon(icon()?)
+            .build()?)
+    }
+}
+
+/// A plain Dota-red square, so the app doesn't need to ship an image file.
+#[cfg(feature = "tray")]
+fn icon() -> Result<Icon, tray_icon::BadIcon> {
+    const SIZE: u32 = 16;
+    let rgba = [0xb4, 0x2a, 0x1e, 0xff].repeat((SIZE * SIZE) as usize);
+    Icon::from_rgba(rgba, SIZE, SIZE)
+}
+
+impl Default for Tray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Tray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "tray")]
+        let available = self._icon.is_some();
+        #[cfg(not(feature = "tray"))]
+        let available = false;
+        f.debug_struct("Tray").field("available", &available).finish()
+    }
+}
+
+/// Stream of chosen tray menu actions, for use with `Subscription::run`.
+#[cfg(feature = "tray")]
+pub fn events() -> impl Stream<Item = TrayAction> {
+    iced::stream::channel(16, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let action = ITEMS.iter().find(|(id, _, _)| event.id == *id);
+            if let Some((_, _, action)) = action {
+                let _ = sender.unbounded_send(*action);
+            }
+        }));
+
+        while let Some(action) = receiver.next().await {
+            let _ = output.send(action).await;
+        }
+    })
+}
+
+#[cfg(not(feature = "tray"))]
+pub fn events() -> impl Stream<Item = TrayAction> {
+    iced::futures::stream::empty()
+}
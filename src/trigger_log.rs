@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use std::{fs::OpenOptions, io::Write, time::SystemTime};
+
+/// Optional append-only record of every callout that fired, for reviewing a
+/// game afterwards. Each line reads `-0:30  2026-10-14 18:02:11 UTC  Pull camp`.
+#[derive(Debug, Default)]
+pub struct TriggerLog {
+    path: Option<String>,
+}
+
+impl TriggerLog {
+    pub fn new(path: Option<String>) -> Self {
+        Self { path }
+    }
+
+    /// Appends a line for `name` firing at `game_second`. Write failures are
+    /// logged and otherwise ignored so they never interrupt the timer.
+    pub fn record(&self, game_second: i64, name: &str, muted: bool) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let line = format_line(game_second, SystemTime::now(), name, muted);
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{line}"));
+        if let Err(err) = result {
+            eprintln!("Failed to write trigger log {path}: {err}");
+        }
+    }
+}
+
+fn format_line(game_second: i64, now: SystemTime, name: &str, muted: bool) -> String {
+    let sign = if game_second < 0 { "-" } else { "" };
+    let secs = game_second.unsigned_abs();
+    let muted = if muted { " (muted)" } else { "" };
+    format!(
+        "{sign}{}:{:02}  {}  {name}{muted}",
+        secs / 60,
+        secs % 60,
+        format_utc(now)
+    )
+}
+
+/// `YYYY-MM-DD HH:MM:SS UTC`.
+fn format_utc(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn lines_read_game_time_then_utc_time_then_name() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_792_000_931);
+        assert_eq!(
+            format_line(-30, now, "Pull camp", false),
+            "-0:30  2026-10-14 18:02:11 UTC  Pull camp"
+        );
+        assert_eq!(
+            format_line(605, now, "Stack", true),
+            "10:05  2026-10-14 18:02:11 UTC  Stack (muted)"
+        );
+    }
+}